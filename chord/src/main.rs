@@ -24,6 +24,7 @@ use corten::simulation::Operation;
 use corten::simulation::utils;
 use corten::simulation::Time;
 use corten::simulation::Conf;
+use corten::Error;
 
 use std::any::Any;
 
@@ -250,7 +251,7 @@ struct ChordConf {
 }
 
 fn default_successor_list_size() -> u8 { 1 }
-fn default_period() -> i32 { 200 }
+fn default_period() -> Time { 200 }
 fn default_count() -> u16 { 100 }
 
 
@@ -765,7 +766,9 @@ fn _save_app_info(apps: &Vec<Rc<RefCell<Box<dyn ApplicationBase>>>>, processes:
         res += &format!("\n{:?}, process_id: {}\npredecessor: {:?}\nsuccessor_list: {:?}\nfingers: {:?}\n", app.id, processes[i as usize].borrow().get_id(), app.predecessor, app.successor_list, app.finger);
     }
 
-    utils::save_to_file(format!("ring-n{}.out", conf.n), res, false);
+    if let Err(e) = utils::save_to_file(format!("ring-n{}.out", conf.n), res, false) {
+        eprintln!("Error: {}", e);
+    }
 }
 
 fn stats(apps: &Vec<Rc<RefCell<Box<dyn ApplicationBase>>>>, conf: &Conf) {
@@ -778,7 +781,9 @@ fn stats(apps: &Vec<Rc<RefCell<Box<dyn ApplicationBase>>>>, conf: &Conf) {
     if let Some(_) = conf.load {
         let filename = format!("latencies-load-n{}.dat", conf.n);
         let latencies = utils::vec_to_one_item_per_line(&app.stats.borrow().latencies);
-        utils::save_to_file(filename, latencies, false);
+        if let Err(e) = utils::save_to_file(filename, latencies, false) {
+            eprintln!("Error: {}", e);
+        }
     }
 }
 
@@ -797,14 +802,21 @@ fn main() {
         ap.parse_args_or_exit();
     }
 
-    let chord_conf: ChordConf = utils::yaml_from_file_to_object(&conf_filename);
+    if let Err(e) = run(conf_filename) {
+        eprintln!("Error: {}", e);
+        ::std::process::exit(-1);
+    }
+}
+
+fn run(conf_filename: String) -> Result<(), Error> {
+    let chord_conf: ChordConf = utils::yaml_from_file_to_object(&conf_filename)?;
 
     let two: ChordId = From::from(2_u32);
     let num_processes: ChordId = From::from(chord_conf.n);
     let max_processes = pow(two, chord_conf.m.into());
     assert!(num_processes <= max_processes, "Number of processes (n) should be less than or equal to 2^m\nThe numbers provided were: n={n} and m={m} so {n} <= {max_proc} is not satisfied", n = chord_conf.n, m = chord_conf.m, max_proc = max_processes);
 
-    let conf: Conf = utils::yaml_from_file_to_object(&conf_filename);
+    let conf: Conf = utils::yaml_from_file_to_object(&conf_filename)?;
 
     let mut apps = Vec::new();
     if let None = &conf.load {
@@ -823,9 +835,11 @@ fn main() {
         }
     }
 
-    let kernel = SimulationKernel::init(&apps, conf_filename);
+    let kernel = SimulationKernel::init(&apps, conf_filename)?;
 
     stats(&kernel.get_applications(), &conf);
 
     //_save_app_info(&kernel.get_applications(), &kernel.get_processes(), &conf);
+
+    Ok(())
 }
@@ -12,6 +12,7 @@ use corten::simulation::SimulationKernel;
 use corten::simulation::ApplicationBase;
 
 use corten::simulation::utils;
+use corten::Error;
 
 use corten::echo_lib::*;
 
@@ -22,13 +23,22 @@ use std::cell::RefCell;
 fn main() {
     let conf_filename = "config/conf-main-end.yaml";
 
-    let app_conf: Rc<AppConf> = Rc::new(utils::yaml_from_file_to_object(&conf_filename));
+    if let Err(e) = run(conf_filename) {
+        eprintln!("Error: {}", e);
+        ::std::process::exit(-1);
+    }
+}
+
+fn run(conf_filename: &str) -> Result<(), Error> {
+    let app_conf: Rc<AppConf> = Rc::new(utils::yaml_from_file_to_object(&conf_filename)?);
 
     let mut apps = Vec::new();
     for i in 0..app_conf.n {
         apps.push(Rc::new(RefCell::new(Box::new(Application::new(i, 0, 0, 0, app_conf.clone())) as Box<dyn ApplicationBase>)));
     }
-    let kernel = SimulationKernel::init(&apps, conf_filename);
+    let kernel = SimulationKernel::init(&apps, conf_filename)?;
 
     stats(&kernel.get_applications());
+
+    Ok(())
 }
@@ -0,0 +1,54 @@
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0>.
+// This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::fmt;
+use std::io;
+use std::error;
+
+/// Errors returned while configuring, running, saving or loading a simulation
+#[derive(Debug)]
+pub enum Error {
+    /// a file could not be opened, read or written
+    Io { file: String, source: io::Error },
+    /// a yaml file does not match the expected structure
+    Yaml { file: String, source: serde_yaml::Error },
+    /// a snapshot could not be encoded or decoded
+    Checkpoint { file: String, source: bincode::Error },
+    /// an invalid value in a configuration, churn or latency file
+    /// line starts at 1 and is known whenever the file is read line by line or entry by entry
+    Config { file: String, line: Option<usize>, msg: String },
+    /// the simulation was asked to do something that it is unable to do
+    Simulation(String)
+}
+
+impl Error {
+    pub fn config<F: Into<String>, M: Into<String>>(file: F, line: Option<usize>, msg: M) -> Self {
+        Error::Config { file: file.into(), line, msg: msg.into() }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Io { file, source } => write!(f, "{}: {}", file, source),
+            Error::Yaml { file, source } => write!(f, "{}: {}", file, source),
+            Error::Checkpoint { file, source } => write!(f, "{}: unable to encode or decode snapshot: {}", file, source),
+            Error::Config { file, line: Some(line), msg } => write!(f, "{}:{}: {}", file, line, msg),
+            Error::Config { file, line: None, msg } => write!(f, "{}: {}", file, msg),
+            Error::Simulation(msg) => write!(f, "{}", msg)
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Error::Io { source, .. } => Some(source),
+            Error::Yaml { source, .. } => Some(source),
+            Error::Checkpoint { source, .. } => Some(source),
+            _ => None
+        }
+    }
+}
@@ -18,6 +18,9 @@ extern crate serde_yaml;
 extern crate bincode;
 extern crate rand_xorshift;
 
+pub mod error;
+pub use crate::error::Error;
+
 pub mod simulation;
 
 pub mod echo_lib;
@@ -14,14 +14,15 @@ use std::cell::RefCell;
 
 use std::any::Any;
 
+use crate::simulation::Time;
 use crate::simulation::wrappers::{NormalWrapper, UniformWrapper, WeibullWrapper, DistributionWrapper};
 
 #[typetag::serde(tag = "type")]
 pub trait Asynchrony: Debug + objekt::Clone {
-    fn get_async(&mut self, rng: Rc<RefCell<XorShiftRng>>, ts: i32) -> i32;
+    fn get_async(&mut self, rng: Rc<RefCell<XorShiftRng>>, ts: Time) -> Time;
 
-    fn calculate_async(&self, ts: i32, async_factor: f64) -> i32 {
-        (ts as f64 * async_factor).round() as i32
+    fn calculate_async(&self, ts: Time, async_factor: f64) -> Time {
+        (ts as f64 * async_factor).round() as Time
     }
 
     fn as_any(&self) -> &dyn Any;
@@ -35,7 +36,7 @@ pub struct NoAsynchrony;
 
 #[typetag::serde]
 impl Asynchrony for NoAsynchrony {
-    fn get_async(&mut self, _rng: Rc<RefCell<XorShiftRng>>, _ts: i32) -> i32 {
+    fn get_async(&mut self, _rng: Rc<RefCell<XorShiftRng>>, _ts: Time) -> Time {
         0
     }
 
@@ -58,7 +59,7 @@ pub struct UniformAsynchrony {
 
 #[typetag::serde]
 impl Asynchrony for UniformAsynchrony {
-    fn get_async(&mut self, rng: Rc<RefCell<XorShiftRng>>, ts: i32) -> i32 {
+    fn get_async(&mut self, rng: Rc<RefCell<XorShiftRng>>, ts: Time) -> Time {
         let async_factor = self.uniform.sample(&mut *rng.borrow_mut());
 
         let asynchrony = self.calculate_async(ts, async_factor);
@@ -91,7 +92,7 @@ pub struct NormalAsynchrony {
 
 #[typetag::serde]
 impl Asynchrony for NormalAsynchrony {
-    fn get_async(&mut self, rng: Rc<RefCell<XorShiftRng>>, ts: i32) -> i32 {
+    fn get_async(&mut self, rng: Rc<RefCell<XorShiftRng>>, ts: Time) -> Time {
         let async_factor = self.normal.sample(&mut *rng.borrow_mut());
 
         let asynchrony = self.calculate_async(ts, async_factor);
@@ -124,7 +125,7 @@ pub struct WeibullAsynchrony {
 
 #[typetag::serde]
 impl Asynchrony for WeibullAsynchrony {
-    fn get_async(&mut self, rng: Rc<RefCell<XorShiftRng>>, ts: i32) -> i32 {
+    fn get_async(&mut self, rng: Rc<RefCell<XorShiftRng>>, ts: Time) -> Time {
         let async_factor = self.weibull.sample(&mut *rng.borrow_mut());
 
         let asynchrony = self.calculate_async(ts, async_factor);
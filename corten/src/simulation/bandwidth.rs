@@ -0,0 +1,207 @@
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0>.
+// This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+use std::cmp;
+
+use serde::{Serialize, Deserialize};
+
+use crate::simulation::{Time, TimeUnit, ProcessId};
+use crate::Error;
+
+/// Capacities of the links between the processes and the network, given by bandwidth in the configuration.
+/// Capacities are in bytes per second, and 0 stands for unlimited.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct BandwidthConf {
+    #[serde(default)]
+    pub uplink: u64,
+    #[serde(default)]
+    pub downlink: u64,
+    /// capacities of some of the processes, see read_capacities for its structure,
+    /// the processes that are not in it have uplink and downlink
+    pub capacities_file: Option<String>
+}
+
+/// Uplink and downlink of each process, through which messages go one at a time.
+/// A message of Operation::size bytes first waits for the uplink of the sender to be free and takes size / uplink to be sent,
+/// then travels with the latency given by the network, and finally waits for the downlink of the target
+/// and takes size / downlink to be received.
+/// The downlink is reserved when the message is sent, in the first period in which it is free from the arrival of the message on,
+/// so a message is never delayed by another one that arrives later.
+#[cfg_attr(feature = "checkpointing", derive(Serialize, Deserialize))]
+#[derive(Debug, Default)]
+pub struct Bandwidth {
+    /// no message is delayed when false
+    enabled: bool,
+    uplink: u64,
+    downlink: u64,
+    /// (uplink, downlink) of the processes in the capacities file, indexed by process id
+    capacities: Vec<Option<(u64, u64)>>,
+    /// nanoseconds in one unit of simulation time
+    unit_nanos: i64,
+    /// time at which the uplink of each process is free to send the next message
+    uplink_free: Vec<Time>,
+    /// periods [start, end) in which the downlink of each process is receiving a message, sorted by start,
+    /// that end after the time at which the last message to the process was sent
+    downlink_busy: Vec<Vec<(Time, Time)>>
+}
+
+impl Bandwidth {
+    pub fn new(conf: &BandwidthConf, time_unit: TimeUnit) -> Result<Self, Error> {
+        let capacities = match &conf.capacities_file {
+            Some(capacities_file) => read_capacities(capacities_file)?,
+            None => Vec::new()
+        };
+        Ok(Bandwidth { enabled: true, uplink: conf.uplink, downlink: conf.downlink, capacities, unit_nanos: time_unit.nanos(), uplink_free: Vec::new(), downlink_busy: Vec::new() })
+    }
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+    pub fn get_uplink(&self, id: ProcessId) -> u64 {
+        match self.capacities.get(id as usize) {
+            Some(Some((uplink, _))) => *uplink,
+            _ => self.uplink
+        }
+    }
+    pub fn get_downlink(&self, id: ProcessId) -> u64 {
+        match self.capacities.get(id as usize) {
+            Some(Some((_, downlink))) => *downlink,
+            _ => self.downlink
+        }
+    }
+    /// time to transmit size bytes at capacity bytes per second, rounded up to a whole unit of simulation time
+    pub fn transmission_time(&self, size: u64, capacity: u64) -> Time {
+        if size == 0 || capacity == 0 {
+            return 0;
+        }
+        let nanos = size as u128 * 1_000_000_000;
+        let per_unit = capacity as u128 * self.unit_nanos as u128;
+        nanos.div_ceil(per_unit) as Time
+    }
+    /// time at which the link is free after transmitting a message that is ready at ts
+    fn transmit(free: &mut Vec<Time>, id: ProcessId, ts: Time, transmission_time: Time) -> Time {
+        if id as usize >= free.len() {
+            free.resize(id as usize + 1, Time::MIN);
+        }
+        let done = cmp::max(ts, free[id as usize]) + transmission_time;
+        free[id as usize] = done;
+        done
+    }
+    /// sender starts sending a message of size bytes at ts, returns the time at which it is fully sent
+    pub fn send(&mut self, ts: Time, sender: ProcessId, size: u64) -> Time {
+        if !self.enabled || size == 0 {
+            return ts;
+        }
+        let transmission_time = self.transmission_time(size, self.get_uplink(sender));
+        Bandwidth::transmit(&mut self.uplink_free, sender, ts, transmission_time)
+    }
+    /// a message of size bytes sent at now starts arriving at target at ts, returns the time at which it is fully received
+    pub fn receive(&mut self, now: Time, ts: Time, target: ProcessId, size: u64) -> Time {
+        if !self.enabled || size == 0 {
+            return ts;
+        }
+        let transmission_time = self.transmission_time(size, self.get_downlink(target));
+        if target as usize >= self.downlink_busy.len() {
+            self.downlink_busy.resize(target as usize + 1, Vec::new());
+        }
+        let busy = &mut self.downlink_busy[target as usize];
+        // messages sent from now on arrive at now or later
+        busy.retain(|&(_, end)| end > now);
+
+        let mut start = ts;
+        let mut index = 0;
+        while index < busy.len() {
+            let (busy_start, busy_end) = busy[index];
+            if busy_start >= start + transmission_time {
+                break;
+            }
+            start = cmp::max(start, busy_end);
+            index += 1;
+        }
+        if transmission_time > 0 {
+            busy.insert(index, (start, start + transmission_time));
+        }
+        start + transmission_time
+    }
+}
+
+/// assumes file with the following structure:
+/// process, uplink, downlink, each separated by '\t', with capacities in bytes per second
+pub fn read_capacities<P: AsRef<Path>>(filename: P) -> Result<Vec<Option<(u64, u64)>>, Error> {
+    let file_display = filename.as_ref().display().to_string();
+    let file = File::open(&filename).map_err(|source| Error::Io { file: file_display.clone(), source })?;
+
+    let mut capacities = Vec::new();
+    for (line_index, line_result) in BufReader::new(file).lines().enumerate() {
+        let line = line_result.map_err(|source| Error::Io { file: file_display.clone(), source })?;
+        let error = |msg: String| Error::config(file_display.clone(), Some(line_index + 1), msg);
+        if line.trim().is_empty() {
+            continue;
+        }
+        let content = line.split('\t').collect::<Vec<&str>>();
+        if content.len() < 3 {
+            return Err(error("expected process, uplink and downlink separated by '\\t'".to_string()));
+        }
+
+        let id = content[0].trim().parse::<ProcessId>().map_err(|_| error("process must be a non-negative integer".to_string()))?;
+        let uplink = content[1].trim().parse::<u64>().map_err(|_| error("uplink must be a non-negative integer".to_string()))?;
+        let downlink = content[2].trim().parse::<u64>().map_err(|_| error("downlink must be a non-negative integer".to_string()))?;
+
+        if id as usize >= capacities.len() {
+            capacities.resize(id as usize + 1, None);
+        }
+        capacities[id as usize] = Some((uplink, downlink));
+    }
+    Ok(capacities)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::simulation::TimeUnit;
+    use crate::simulation::bandwidth::{Bandwidth, BandwidthConf};
+
+    #[test]
+    fn test_bandwidth() {
+        let conf = BandwidthConf { uplink: 1000, downlink: 4000, capacities_file: None };
+        let mut bandwidth = Bandwidth::new(&conf, TimeUnit::Ms).unwrap();
+        assert_eq!(bandwidth.transmission_time(1, 1000), 1);
+        assert_eq!(bandwidth.transmission_time(1500, 1000), 1500);
+        assert_eq!(bandwidth.transmission_time(1500, 0), 0);
+
+        // messages queue in the uplink of the sender
+        assert_eq!(bandwidth.send(0, 0, 1000), 1000);
+        assert_eq!(bandwidth.send(0, 0, 1000), 2000);
+        assert_eq!(bandwidth.send(5000, 0, 500), 5500);
+        assert_eq!(bandwidth.send(0, 1, 1000), 1000);
+        assert_eq!(bandwidth.send(0, 1, 0), 0);
+
+        // and in the downlink of the target
+        assert_eq!(bandwidth.receive(0, 100, 2, 4000), 1100);
+        assert_eq!(bandwidth.receive(0, 200, 2, 4000), 2100);
+        assert_eq!(bandwidth.receive(0, 3000, 2, 4000), 4000);
+        // a message that arrives earlier, even if sent later, uses a free period of the downlink before the others
+        assert_eq!(bandwidth.receive(0, 10_000, 3, 4000), 11_000);
+        assert_eq!(bandwidth.receive(10, 15, 3, 4000), 1015);
+        assert_eq!(bandwidth.receive(20, 9500, 3, 4000), 11_000 + 1000);
+        assert_eq!(bandwidth.receive(30, 8000, 3, 2000), 8500);
+
+        let disabled = &mut Bandwidth::default();
+        assert_eq!(disabled.send(10, 0, 1_000_000), 10);
+        assert_eq!(disabled.receive(0, 10, 0, 1_000_000), 10);
+
+        let conf = BandwidthConf { uplink: 1000, downlink: 0, capacities_file: Some("config/test/capacities.txt".to_string()) };
+        let mut bandwidth = Bandwidth::new(&conf, TimeUnit::S).unwrap();
+        assert_eq!((bandwidth.get_uplink(0), bandwidth.get_downlink(0)), (1000, 0));
+        assert_eq!((bandwidth.get_uplink(1), bandwidth.get_downlink(1)), (125_000, 1_250_000));
+        assert_eq!((bandwidth.get_uplink(5), bandwidth.get_downlink(5)), (1000, 0));
+        assert_eq!(bandwidth.send(0, 0, 1500), 2);
+        assert_eq!(bandwidth.receive(0, 0, 1, 2_500_000), 2);
+
+        let conf = BandwidthConf { uplink: 1000, downlink: 0, capacities_file: Some("config/test/process-sites.txt".to_string()) };
+        assert!(Bandwidth::new(&conf, TimeUnit::Ms).is_err());
+    }
+}
@@ -0,0 +1,265 @@
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0>.
+// This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::fmt::Display;
+use std::path::Path;
+
+use std::rc::Rc;
+use std::cell::RefCell;
+
+use crate::simulation::{Conf, SimulationKernel, ApplicationBase, ProcessId, Time, TieBreaking, TimeUnit, BandwidthConf, NetworkOverride, AsyncProfile, ClockConf, FailureDetectorConf, DomainsConf, ApplicationFactory};
+use crate::simulation::network::Network;
+use crate::simulation::bandwidth::Bandwidth;
+use crate::simulation::asynchrony::Asynchrony;
+use crate::simulation::utils;
+use crate::Error;
+
+/// Builds a SimulationKernel from a configuration held in memory.
+///
+/// The kernel returned by build is configured (processes, churn and save events)
+/// but not yet running, so it can be further configured before calling run.
+#[derive(Debug)]
+pub struct SimulationBuilder {
+    conf: Conf,
+    factory: Option<ApplicationFactory>
+}
+
+impl SimulationBuilder {
+    /// starts from the default configuration for n processes
+    pub fn new(n: ProcessId) -> Self {
+        SimulationBuilder { conf: Conf::new(n), factory: None }
+    }
+    pub fn from_conf(conf: Conf) -> Self {
+        SimulationBuilder { conf, factory: None }
+    }
+    pub fn from_file<P: AsRef<Path> + Display>(conf_filename: P) -> Result<Self, Error> {
+        Ok(SimulationBuilder { conf: utils::yaml_from_file_to_object(&conf_filename)?, factory: None })
+    }
+    pub fn network(mut self, network: Box<dyn Network>) -> Self {
+        self.conf.network = network;
+        self
+    }
+    pub fn asynchrony(mut self, asynchrony: Box<dyn Asynchrony>) -> Self {
+        self.conf.asynchrony = asynchrony;
+        self
+    }
+    pub fn op_duration(mut self, op_duration: Time) -> Self {
+        self.conf.op_duration = Some(op_duration);
+        self
+    }
+    pub fn churn_file<S: Into<String>>(mut self, churn_file: S) -> Self {
+        self.conf.churn_file = Some(churn_file.into());
+        self
+    }
+    /// churn schedule with the same format as a churn file, for e.g. "churn:\n  - [0, join, 1.0]"
+    pub fn churn<S: Into<String>>(mut self, churn: S) -> Self {
+        self.conf.churn = Some(churn.into());
+        self
+    }
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.conf.seed = seed;
+        self
+    }
+    /// ts is the time at which a snapshot of the simulation is saved
+    pub fn save(mut self, ts: Time) -> Self {
+        self.conf.save = Some(ts);
+        self
+    }
+    pub fn save_and_stop(mut self, save_and_stop: bool) -> Self {
+        self.conf.save_and_stop = save_and_stop;
+        self
+    }
+    pub fn save_filename<S: Into<String>>(mut self, save_filename: S) -> Self {
+        self.conf.save_filename = save_filename.into();
+        self
+    }
+    /// resumes the simulation from the snapshot saved in load_filename
+    pub fn load<S: Into<String>>(mut self, load_filename: S) -> Self {
+        self.conf.load = Some(load_filename.into());
+        self
+    }
+    /// seed to use after loading a snapshot, instead of the saved Random Number Generator
+    pub fn new_seed(mut self, new_seed: u64) -> Self {
+        self.conf.new_seed = Some(new_seed);
+        self
+    }
+    pub fn tie_breaking(mut self, tie_breaking: TieBreaking) -> Self {
+        self.conf.tie_breaking = tie_breaking;
+        self
+    }
+    pub fn time_unit(mut self, time_unit: TimeUnit) -> Self {
+        self.conf.time_unit = time_unit;
+        self
+    }
+    /// limits the uplink and downlink of the processes
+    pub fn bandwidth(mut self, bandwidth: BandwidthConf) -> Self {
+        self.conf.bandwidth = Some(bandwidth);
+        self
+    }
+    /// gives the processes local clocks with offset and drift
+    pub fn clock(mut self, clock: ClockConf) -> Self {
+        self.conf.clock = Some(clock);
+        self
+    }
+    pub fn failure_detector(mut self, failure_detector: FailureDetectorConf) -> Self {
+        self.conf.failure_detector = Some(failure_detector);
+        self
+    }
+    /// forbids the processes to read the global membership
+    pub fn strict(mut self, strict: bool) -> Self {
+        self.conf.strict = strict;
+        self
+    }
+    /// processes returned by Process::get_introducer
+    pub fn introducers(mut self, introducers: Vec<ProcessId>) -> Self {
+        self.conf.introducers = introducers;
+        self
+    }
+    /// creates the applications of the processes spawned by the spawn churn action,
+    /// which is not saved in snapshots and so must also be given when loading one
+    pub fn application_factory(mut self, factory: ApplicationFactory) -> Self {
+        self.factory = Some(factory);
+        self
+    }
+    /// failure domains of the processes, for the churn actions fail-domain, leave-domain and recover-domain
+    pub fn domains(mut self, domains: DomainsConf) -> Self {
+        self.conf.domains = Some(domains);
+        self
+    }
+    /// adds an async profile, that can be given to processes from the start or by the profile churn action
+    pub fn async_profile(mut self, async_profile: AsyncProfile) -> Self {
+        self.conf.async_profiles.push(async_profile);
+        self
+    }
+    /// adds a change to the network, for some processes or links and possibly only during some time
+    pub fn network_override(mut self, network_override: NetworkOverride) -> Self {
+        self.conf.network_overrides.push(network_override);
+        self
+    }
+    pub fn conf(&self) -> &Conf {
+        &self.conf
+    }
+    pub fn into_conf(self) -> Conf {
+        self.conf
+    }
+    /// creates the kernel with one process per application, and schedules the churn and save events
+    /// when loading a snapshot apps is ignored, as the applications are restored from the snapshot
+    pub fn build(&self, apps: &Vec<Rc<RefCell<Box<dyn ApplicationBase>>>>) -> Result<SimulationKernel, Error> {
+        let conf = &self.conf;
+        if let Some(failure_detector) = &conf.failure_detector {
+            failure_detector.validate().map_err(|msg| Error::config("failure_detector", None, msg))?;
+        }
+
+        let mut kernel;
+        if conf.load.is_some() {
+            kernel = SimulationKernel::load_state(conf)?;
+
+            if let Some(seed) = conf.new_seed {
+                println!("After load, running with new seed {}", seed);
+            } else {
+                println!("After load, running with same Random Number Generator");
+            }
+        } else {
+            if conf.n as usize != apps.len() {
+                return Err(Error::Simulation(format!("SimulationBuilder::build must receive a Vec of applications with size equal to n={}, as it is specified in the configuration, but received {}", conf.n, apps.len())));
+            }
+
+            let op_duration = SimulationKernel::get_op_duration(conf)?;
+
+            kernel = SimulationKernel::new(conf);
+            if let Some(bandwidth) = &conf.bandwidth {
+                *kernel.bandwidth.borrow_mut() = Bandwidth::new(bandwidth, conf.time_unit)?;
+            }
+
+            let asynchrony = Rc::new(RefCell::new(conf.asynchrony.clone()));
+            let mut network = conf.network.clone();
+            network.set_time_unit(conf.time_unit);
+            let network = Rc::new(RefCell::new(network));
+            //init process state
+            for i in 0..conf.n {
+                kernel.add_process(i as ProcessId, apps[i as usize].clone(), op_duration, asynchrony.clone(), network.clone());
+            }
+            if let Some(failure_detector) = &conf.failure_detector {
+                for i in 0..conf.n {
+                    kernel.set_failure_detector(i as ProcessId, failure_detector.clone());
+                }
+            }
+            if let Some(clock) = &conf.clock {
+                let clocks = clock.get_clocks(conf.n, conf.seed).map_err(Error::Simulation)?;
+                for (i, clock) in clocks.into_iter().enumerate() {
+                    kernel.set_clock(i as ProcessId, clock);
+                }
+            }
+
+            println!("Running with seed {}", conf.seed);
+        }
+
+        let num_processes = kernel.get_processes().len() as ProcessId;
+        if let Some(id) = conf.introducers.iter().find(|&&id| id >= num_processes) {
+            return Err(Error::Simulation(format!("introducer {} does not exist, ids must be in 0..{}", id, num_processes)));
+        }
+        if conf.strict && conf.introducers.is_empty() {
+            return Err(Error::Simulation("strict mode requires introducers, as the processes can not read the global membership to find one".to_string()));
+        }
+        if let Some(factory) = &self.factory {
+            kernel.set_application_factory(factory.clone());
+        }
+        let simulation_stops = kernel.config(conf)?;
+
+        kernel.update_process_simulation_stops(simulation_stops);
+        kernel.update_process_strict(conf.strict, &conf.introducers);
+
+        Ok(kernel)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::rc::Rc;
+
+    use crate::simulation::{SimulationBuilder, SimulationKernel, Conf};
+    use crate::simulation::asynchrony::NoAsynchrony;
+    use crate::simulation::network::{ConstantNetwork, NoJitter};
+    use crate::simulation::utils;
+    use crate::simulation::test::echo_apps;
+
+    use crate::echo_lib::{AppConf, stats};
+
+    #[test]
+    fn test_builder_same_as_conf_file() {
+        let conf_filename = "config/test/conf-churn.yaml";
+        let app_conf: Rc<AppConf> = Rc::new(utils::yaml_from_file_to_object(&conf_filename).unwrap());
+
+        let kernel = SimulationKernel::init(&echo_apps(app_conf.clone()), conf_filename).unwrap();
+
+        let builder = SimulationBuilder::new(app_conf.n)
+            .network(Box::new(ConstantNetwork::new(100, Box::new(NoJitter), 0.0)))
+            .asynchrony(Box::new(NoAsynchrony::new()))
+            .op_duration(200)
+            .churn_file("config/churn-test.yaml")
+            .seed(0);
+        let mut kernel2 = builder.build(&echo_apps(app_conf.clone())).unwrap();
+        kernel2.run(builder.conf()).unwrap();
+
+        assert_eq!(stats(kernel.get_applications()), stats(kernel2.get_applications()));
+    }
+
+    #[test]
+    fn test_builder_in_memory_churn() {
+        let app_conf = Rc::new(AppConf::new(5, 2, 3, 100));
+
+        let builder = SimulationBuilder::from_conf(Conf::new(5))
+            .asynchrony(Box::new(NoAsynchrony::new()))
+            .churn("churn:\n  - [0, join, 1.0]\n  - [150, end]");
+        let mut kernel = builder.build(&echo_apps(app_conf)).unwrap();
+
+        assert_eq!(kernel.get_processes_up().len(), 0);
+
+        kernel.run(builder.conf()).unwrap();
+
+        assert_eq!(kernel.get_processes_up().len(), 5);
+        assert_eq!(*kernel.current_ts.borrow(), 150);
+    }
+}
@@ -0,0 +1,132 @@
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0>.
+// This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use rand::prelude::*;
+use rand_xorshift::XorShiftRng;
+
+use serde::{Serialize, Deserialize};
+
+use crate::simulation::{Time, ProcessId};
+use crate::simulation::utils;
+
+/// Local clocks of the processes, given by clock in the configuration.
+/// Each process gets offset and drift, plus a value taken uniformly at random in [-max_offset, max_offset]
+/// and [-max_drift, max_drift], unless it is listed in processes.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct ClockConf {
+    #[serde(default)]
+    pub offset: Time,
+    #[serde(default)]
+    pub drift: f64,
+    #[serde(default)]
+    pub max_offset: Time,
+    #[serde(default)]
+    pub max_drift: f64,
+    /// clocks of some of the processes, that are used as given
+    #[serde(default)]
+    pub processes: Vec<ProcessClock>
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ProcessClock {
+    pub id: ProcessId,
+    #[serde(default)]
+    pub offset: Time,
+    #[serde(default)]
+    pub drift: f64
+}
+
+impl ClockConf {
+    /// clock of each of the n processes, taken at random from seed so that it does not depend on the
+    /// Random Number Generator of the simulation
+    pub fn get_clocks(&self, n: ProcessId, seed: u64) -> Result<Vec<Clock>, String> {
+        (0..n).map(|id| self.get_clock(id, seed)).collect()
+    }
+    /// checks that processes only has clocks of processes with ids in 0..max_id, which include
+    /// the processes spawned during the simulation
+    pub fn check_ids(&self, max_id: ProcessId) -> Result<(), String> {
+        match self.processes.iter().find(|p| p.id >= max_id) {
+            Some(process) => Err(format!("clock of process {} that does not exist, ids must be in 0..{}", process.id, max_id)),
+            None => Ok(())
+        }
+    }
+    /// clock of process id, which is the same as in get_clocks, also for processes spawned during the simulation
+    pub fn get_clock(&self, id: ProcessId, seed: u64) -> Result<Clock, String> {
+        if let Some(process) = self.processes.iter().rev().find(|p| p.id == id) {
+            return Clock::new(process.offset, process.drift);
+        }
+        let mut rng = XorShiftRng::seed_from_u64(utils::mix64(seed ^ utils::mix64(id as u64)));
+        let offset = if self.max_offset > 0 { rng.gen_range(-self.max_offset, self.max_offset + 1) } else { 0 };
+        let drift = if self.max_drift > 0.0 { rng.gen_range(-self.max_drift, self.max_drift) } else { 0.0 };
+        Clock::new(self.offset + offset, self.drift + drift)
+    }
+}
+
+/// Local clock of a process, that reads offset + t * (1 + drift) at global time t,
+/// for e.g. drift 0.001 makes the clock gain one unit of time every 1000 units.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+pub struct Clock {
+    offset: Time,
+    drift: f64
+}
+
+impl Clock {
+    pub fn new(offset: Time, drift: f64) -> Result<Self, String> {
+        if drift <= -1.0 {
+            return Err(format!("clock drift must be greater than -1, but it is {}", drift));
+        }
+        Ok(Clock { offset, drift })
+    }
+    pub fn get_offset(&self) -> Time {
+        self.offset
+    }
+    pub fn get_drift(&self) -> f64 {
+        self.drift
+    }
+    /// time read by the clock at global time ts
+    pub fn local_time(&self, ts: Time) -> Time {
+        ts + self.offset + (ts as f64 * self.drift).round() as Time
+    }
+    /// global time that passes while the clock advances delta
+    pub fn global_delta(&self, delta: Time) -> Time {
+        if self.drift == 0.0 {
+            return delta;
+        }
+        (delta as f64 / (1.0 + self.drift)).round() as Time
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::simulation::clock::{Clock, ClockConf, ProcessClock};
+
+    #[test]
+    fn test_clock() {
+        let clock = Clock::default();
+        assert_eq!(clock.local_time(1234), 1234);
+        assert_eq!(clock.global_delta(100), 100);
+
+        let clock = Clock::new(-50, 0.25).unwrap();
+        assert_eq!(clock.local_time(0), -50);
+        assert_eq!(clock.local_time(400), 450);
+        assert_eq!(clock.global_delta(100), 80);
+        let clock = Clock::new(0, -0.5).unwrap();
+        assert_eq!(clock.local_time(400), 200);
+        assert_eq!(clock.global_delta(100), 200);
+        assert!(Clock::new(0, -1.0).is_err());
+
+        let conf = ClockConf { offset: 10, drift: 0.0, max_offset: 5, max_drift: 0.01, processes: vec![ProcessClock { id: 2, offset: 0, drift: 0.5 }] };
+        let clocks = conf.get_clocks(4, 7).unwrap();
+        assert_eq!(clocks, conf.get_clocks(4, 7).unwrap());
+        assert_eq!(clocks[2], Clock::new(0, 0.5).unwrap());
+        for clock in clocks.iter().take(2) {
+            assert!(clock.get_offset() >= 5 && clock.get_offset() <= 15);
+            assert!(clock.get_drift().abs() < 0.01);
+        }
+        assert!(conf.check_ids(2).is_err());
+        assert!(conf.check_ids(3).is_ok());
+        assert_eq!(conf.get_clock(1, 7), Ok(clocks[1]));
+    }
+}
@@ -0,0 +1,369 @@
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0>.
+// This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::rc::Rc;
+use std::cell::RefCell;
+use std::collections::{BTreeMap, VecDeque};
+
+use serde::{Serialize, Deserialize};
+
+use crate::simulation::{ApplicationBase, Operation, Process, ProcessId, Time, TimerId};
+use crate::simulation::observer;
+
+/// Failure detector of the processes, given by failure_detector in the configuration.
+/// Every period a process sends a heartbeat request to each process it monitors, which replies with a heartbeat,
+/// so heartbeats are delayed and lost like any other message.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum FailureDetectorConf {
+    /// suspects a process when no heartbeat arrived from it for timeout
+    Heartbeat { period: Time, timeout: Time },
+    /// suspects a process when phi exceeds threshold, where phi is -log10 of the probability that the next heartbeat
+    /// is still to arrive, assuming that the time between heartbeats follows a normal distribution with the mean
+    /// and standard deviation of the last window times
+    PhiAccrual {
+        period: Time,
+        #[serde(default = "threshold_default")]
+        threshold: f64,
+        #[serde(default = "window_default")]
+        window: usize,
+        /// by default period / 4, or 1 when period is less than 4
+        #[serde(default)]
+        min_std_dev: Option<Time>
+    }
+}
+
+fn threshold_default() -> f64 {
+    8.0
+}
+
+fn window_default() -> usize {
+    100
+}
+
+impl Default for FailureDetectorConf {
+    fn default() -> Self {
+        FailureDetectorConf::Heartbeat { period: 1000, timeout: 3000 }
+    }
+}
+
+impl FailureDetectorConf {
+    pub fn heartbeat(period: Time, timeout: Time) -> Result<Self, String> {
+        let conf = FailureDetectorConf::Heartbeat { period, timeout };
+        conf.validate()?;
+        Ok(conf)
+    }
+    pub fn phi_accrual(period: Time, threshold: f64, window: usize, min_std_dev: Option<Time>) -> Result<Self, String> {
+        let conf = FailureDetectorConf::PhiAccrual { period, threshold, window, min_std_dev };
+        conf.validate()?;
+        Ok(conf)
+    }
+    /// checks a configuration that was not built with heartbeat or phi_accrual, for e.g. one read from a file,
+    /// as the detector never advances with period 0 and phi is not a number with min_std_dev 0
+    pub fn validate(&self) -> Result<(), String> {
+        match self {
+            FailureDetectorConf::Heartbeat { period, .. } | FailureDetectorConf::PhiAccrual { period, .. } if *period <= 0 => {
+                Err(format!("the period of the failure detector must be positive, but it is {}", period))
+            },
+            FailureDetectorConf::Heartbeat { timeout, .. } if *timeout <= 0 => {
+                Err(format!("the timeout of the failure detector must be positive, but it is {}", timeout))
+            },
+            FailureDetectorConf::PhiAccrual { min_std_dev: Some(min_std_dev), .. } if *min_std_dev <= 0 => {
+                Err(format!("min_std_dev of the failure detector must be positive, but it is {}", min_std_dev))
+            },
+            _ => Ok(())
+        }
+    }
+    pub fn get_period(&self) -> Time {
+        match self {
+            FailureDetectorConf::Heartbeat { period, .. } | FailureDetectorConf::PhiAccrual { period, .. } => *period
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct MonitorState {
+    /// time of the last heartbeat, or at which the monitoring started
+    last: Time,
+    /// times between the last heartbeats
+    intervals: VecDeque<Time>,
+    suspected: bool
+}
+
+/// Processes monitored by a process and which of them it suspects, with times read by the local clock of the process
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct FailureDetector {
+    conf: FailureDetectorConf,
+    monitored: BTreeMap<ProcessId, MonitorState>,
+    /// periodic call that sends the heartbeat requests and checks for suspicions, while some process is monitored
+    timer: Option<TimerId>
+}
+
+impl FailureDetector {
+    pub fn new(conf: FailureDetectorConf) -> Self {
+        FailureDetector { conf, monitored: BTreeMap::new(), timer: None }
+    }
+    pub fn get_conf(&self) -> &FailureDetectorConf {
+        &self.conf
+    }
+    /// returns false if id was already monitored
+    pub fn monitor(&mut self, id: ProcessId, now: Time) -> bool {
+        if self.monitored.contains_key(&id) {
+            return false;
+        }
+        self.monitored.insert(id, MonitorState { last: now, intervals: VecDeque::new(), suspected: false });
+        true
+    }
+    pub fn unmonitor(&mut self, id: ProcessId) {
+        self.monitored.remove(&id);
+    }
+    pub fn get_monitored(&self) -> Vec<ProcessId> {
+        self.monitored.keys().cloned().collect()
+    }
+    pub fn is_suspected(&self, id: ProcessId) -> bool {
+        self.monitored.get(&id).is_some_and(|state| state.suspected)
+    }
+    pub fn get_suspected(&self) -> Vec<ProcessId> {
+        self.monitored.iter().filter(|(_, state)| state.suspected).map(|(id, _)| *id).collect()
+    }
+    /// a heartbeat of id arrived at now, returns true if id was suspected
+    pub fn heartbeat(&mut self, id: ProcessId, now: Time) -> bool {
+        let window = match self.conf {
+            FailureDetectorConf::PhiAccrual { window, .. } => window,
+            FailureDetectorConf::Heartbeat { .. } => 0
+        };
+        match self.monitored.get_mut(&id) {
+            Some(state) => {
+                if window > 0 {
+                    state.intervals.push_back(now - state.last);
+                    while state.intervals.len() > window {
+                        state.intervals.pop_front();
+                    }
+                }
+                state.last = now;
+                let restored = state.suspected;
+                state.suspected = false;
+                restored
+            },
+            None => false
+        }
+    }
+    /// phi of id at now, None if id is not monitored or the detector is not phi accrual
+    pub fn phi(&self, id: ProcessId, now: Time) -> Option<f64> {
+        let (period, min_std_dev) = match self.conf {
+            FailureDetectorConf::PhiAccrual { period, min_std_dev, .. } => (period, min_std_dev.unwrap_or((period / 4).max(1))),
+            FailureDetectorConf::Heartbeat { .. } => return None
+        };
+        let state = self.monitored.get(&id)?;
+        let (mean, std_dev) = if state.intervals.is_empty() {
+            (period as f64, min_std_dev as f64)
+        } else {
+            let n = state.intervals.len() as f64;
+            let mean = state.intervals.iter().sum::<Time>() as f64 / n;
+            let variance = state.intervals.iter().map(|&i| (i as f64 - mean).powi(2)).sum::<f64>() / n;
+            (mean, variance.sqrt().max(min_std_dev as f64))
+        };
+        Some(phi((now - state.last) as f64, mean, std_dev))
+    }
+    /// suspects the monitored processes whose heartbeats are late at now, returns the ones that were not yet suspected
+    pub fn check(&mut self, now: Time) -> Vec<ProcessId> {
+        let mut late = Vec::new();
+        for (&id, state) in &self.monitored {
+            let suspect = match self.conf {
+                FailureDetectorConf::Heartbeat { timeout, .. } => now - state.last > timeout,
+                FailureDetectorConf::PhiAccrual { threshold, .. } => self.phi(id, now).is_some_and(|phi| phi > threshold)
+            };
+            if suspect && !state.suspected {
+                late.push(id);
+            }
+        }
+        for id in &late {
+            self.monitored.get_mut(id).unwrap().suspected = true;
+        }
+        late
+    }
+    /// stops monitoring every process, for e.g. when the process recovers
+    pub fn reset(&mut self) {
+        self.monitored.clear();
+        self.timer = None;
+    }
+}
+
+/// -log10 of the probability that a value of the normal distribution is greater than elapsed,
+/// with the logistic approximation of its cumulative distribution function
+fn phi(elapsed: f64, mean: f64, std_dev: f64) -> f64 {
+    let y = (elapsed - mean) / std_dev;
+    let e = (-y * (1.5976 + 0.070566 * y * y)).exp();
+    if elapsed > mean {
+        -(e / (1.0 + e)).log10()
+    } else {
+        -(1.0 - 1.0 / (1.0 + e)).log10()
+    }
+}
+
+impl Process {
+    /// starts monitoring id with the failure detector, which calls ApplicationBase::on_suspect and
+    /// ApplicationBase::on_restore when its suspicion of id changes
+    /// the detector repeats forever while some process is monitored, so the simulation must have a stop/end event
+    pub fn monitor(&self, id: ProcessId) {
+        let now = self.get_time();
+        let start = {
+            let mut detector = self.detector.borrow_mut();
+            detector.monitor(id, now) && detector.timer.is_none()
+        };
+        if start {
+            let period = self.detector.borrow().conf.get_period();
+            let timer = self.periodic(Box::new(DetectorTick), period, 0);
+            self.detector.borrow_mut().timer = Some(timer);
+        }
+    }
+    pub fn unmonitor(&self, id: ProcessId) {
+        let mut detector = self.detector.borrow_mut();
+        detector.unmonitor(id);
+        if detector.monitored.is_empty() {
+            if let Some(timer) = detector.timer.take() {
+                self.cancel(timer);
+            }
+        }
+    }
+    /// whether the failure detector suspects that id failed
+    pub fn is_suspected(&self, id: ProcessId) -> bool {
+        self.detector.borrow().is_suspected(id)
+    }
+    pub fn get_suspected(&self) -> Vec<ProcessId> {
+        self.detector.borrow().get_suspected()
+    }
+    pub fn get_failure_detector(&self) -> FailureDetector {
+        self.detector.borrow().clone()
+    }
+}
+
+fn notify_suspicion(app: &Rc<RefCell<Box<dyn ApplicationBase>>>, process: &Rc<RefCell<Process>>, id: ProcessId, suspected: bool) {
+    {
+        let p = process.borrow();
+        let ts = *p.current_ts.borrow();
+        observer::notify(&p.observers, |o| if suspected { o.on_suspect(ts, p.id, id) } else { o.on_restore(ts, p.id, id) });
+    }
+    if suspected {
+        app.borrow_mut().on_suspect(process.clone(), id);
+    } else {
+        app.borrow_mut().on_restore(process.clone(), id);
+    }
+}
+
+/// the periodic call of the failure detector
+#[cfg_attr(feature = "checkpointing", derive(Serialize, Deserialize))]
+#[derive(Debug)]
+pub(crate) struct DetectorTick;
+
+#[cfg_attr(feature = "checkpointing", typetag::serde)]
+impl Operation for DetectorTick {
+    fn invoke(&self, app: Rc<RefCell<Box<dyn ApplicationBase>>>, process: Rc<RefCell<Process>>) {
+        let (monitored, suspected) = {
+            let p = process.borrow();
+            let now = p.get_time();
+            let mut detector = p.detector.borrow_mut();
+            (detector.get_monitored(), detector.check(now))
+        };
+        let from = process.borrow().get_id();
+        for id in monitored {
+            process.borrow().send(Box::new(HeartbeatRequest { from }), id);
+        }
+        for id in suspected {
+            notify_suspicion(&app, &process, id, true);
+        }
+    }
+}
+
+#[cfg_attr(feature = "checkpointing", derive(Serialize, Deserialize))]
+#[derive(Debug)]
+pub(crate) struct HeartbeatRequest {
+    from: ProcessId
+}
+
+#[cfg_attr(feature = "checkpointing", typetag::serde)]
+impl Operation for HeartbeatRequest {
+    fn invoke(&self, _app: Rc<RefCell<Box<dyn ApplicationBase>>>, process: Rc<RefCell<Process>>) {
+        let p = process.borrow();
+        p.send(Box::new(Heartbeat { from: p.get_id() }), self.from);
+    }
+}
+
+#[cfg_attr(feature = "checkpointing", derive(Serialize, Deserialize))]
+#[derive(Debug)]
+pub(crate) struct Heartbeat {
+    from: ProcessId
+}
+
+#[cfg_attr(feature = "checkpointing", typetag::serde)]
+impl Operation for Heartbeat {
+    fn invoke(&self, app: Rc<RefCell<Box<dyn ApplicationBase>>>, process: Rc<RefCell<Process>>) {
+        let now = process.borrow().get_time();
+        let restored = process.borrow().detector.borrow_mut().heartbeat(self.from, now);
+        if restored {
+            notify_suspicion(&app, &process, self.from, false);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::simulation::detector::{FailureDetector, FailureDetectorConf};
+
+    #[test]
+    fn test_heartbeat_detector() {
+        let mut detector = FailureDetector::new(FailureDetectorConf::Heartbeat { period: 100, timeout: 250 });
+        assert!(detector.monitor(1, 0));
+        assert!(!detector.monitor(1, 0));
+        detector.monitor(2, 0);
+        assert!(detector.check(200).is_empty());
+        assert!(!detector.heartbeat(1, 200));
+        assert_eq!(detector.check(300), vec![2]);
+        assert!(detector.check(300).is_empty());
+        assert_eq!(detector.get_suspected(), vec![2]);
+        assert!(detector.heartbeat(2, 320));
+        assert!(!detector.is_suspected(2));
+        assert_eq!(detector.check(460), vec![1]);
+        assert_eq!(detector.phi(1, 460), None);
+
+        detector.unmonitor(1);
+        assert_eq!(detector.get_monitored(), vec![2]);
+        assert!(!detector.heartbeat(1, 500));
+    }
+
+    #[test]
+    fn test_detector_conf() {
+        assert_eq!(FailureDetectorConf::heartbeat(100, 250), Ok(FailureDetectorConf::Heartbeat { period: 100, timeout: 250 }));
+        assert!(FailureDetectorConf::heartbeat(0, 250).is_err());
+        assert!(FailureDetectorConf::heartbeat(100, 0).is_err());
+        assert!(FailureDetectorConf::phi_accrual(-1, 8.0, 100, None).is_err());
+        assert!(FailureDetectorConf::phi_accrual(100, 8.0, 100, Some(0)).is_err());
+
+        // the default min_std_dev is positive also for short periods
+        let mut detector = FailureDetector::new(FailureDetectorConf::phi_accrual(2, 8.0, 10, None).unwrap());
+        detector.monitor(1, 0);
+        for i in 1..=5 {
+            detector.heartbeat(1, i * 2);
+        }
+        assert!(detector.phi(1, 12).unwrap().is_finite());
+    }
+
+    #[test]
+    fn test_phi_accrual_detector() {
+        let mut detector = FailureDetector::new(FailureDetectorConf::PhiAccrual { period: 100, threshold: 3.0, window: 10, min_std_dev: Some(10) });
+        detector.monitor(1, 0);
+        for i in 1..=20 {
+            detector.heartbeat(1, i * 100);
+        }
+        let phi = |t| detector.phi(1, t).unwrap();
+        assert!(phi(2000) < 0.01);
+        assert!(phi(2100) > 0.2 && phi(2100) < 0.4);
+        assert!(phi(2110) < phi(2120) && phi(2120) < phi(2150));
+        assert!(detector.check(2120).is_empty());
+        assert_eq!(detector.check(2150), vec![1]);
+        assert!(detector.heartbeat(1, 2160));
+        assert!(!detector.is_suspected(1));
+    }
+}
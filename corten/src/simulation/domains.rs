@@ -0,0 +1,88 @@
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0>.
+// This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use serde::{Serialize, Deserialize};
+
+use crate::Error;
+use crate::simulation::ProcessId;
+use crate::simulation::network::{self, NO_GROUP};
+
+/// Failure domains of the processes, for e.g. racks, datacenters or autonomous systems, given by domains in the configuration,
+/// whose processes fail, leave or recover together with the churn actions fail-domain, leave-domain and recover-domain.
+/// Process id is in the domain given by file, or else in domain id % count.
+/// file has the same structure as the sites_file of a SiteNetwork (see network::read_process_groups),
+/// so the same file can also place the processes in the sites of the network.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct DomainsConf {
+    pub count: ProcessId,
+    #[serde(default)]
+    pub file: Option<String>
+}
+
+/// Domain of each process, where there are no domains unless they are given in the configuration
+#[derive(Debug, Clone, Default)]
+pub struct FailureDomains {
+    count: ProcessId,
+    /// domain of each process, processes that are not in it are in domain id % count
+    process_domains: Vec<ProcessId>
+}
+
+impl FailureDomains {
+    pub fn new(conf: &DomainsConf) -> Result<Self, Error> {
+        if conf.count == 0 {
+            return Err(Error::Simulation("there must be at least one failure domain".to_string()));
+        }
+        let process_domains = match &conf.file {
+            Some(file) => network::read_process_groups(file, conf.count)?,
+            None => Vec::new()
+        };
+        Ok(FailureDomains { count: conf.count, process_domains })
+    }
+    /// number of domains, which go from 0 to len-1
+    pub fn len(&self) -> ProcessId {
+        self.count
+    }
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+    /// domain of process id, None when there are no domains
+    pub fn get_domain(&self, id: ProcessId) -> Option<ProcessId> {
+        if self.count == 0 {
+            return None;
+        }
+        match self.process_domains.get(id as usize) {
+            Some(&domain) if domain != NO_GROUP => Some(domain),
+            _ => Some(id % self.count)
+        }
+    }
+    /// domain of each process given in the file, see network::read_process_groups
+    pub fn get_process_domains(&self) -> &[ProcessId] {
+        &self.process_domains
+    }
+    /// ids of the n processes that are in domain
+    pub fn get_processes(&self, domain: ProcessId, n: ProcessId) -> Vec<ProcessId> {
+        (0..n).filter(|&id| self.get_domain(id) == Some(domain)).collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::simulation::domains::{DomainsConf, FailureDomains};
+
+    #[test]
+    fn test_failure_domains() {
+        assert_eq!(FailureDomains::default().get_domain(3), None);
+        assert!(FailureDomains::new(&DomainsConf { count: 0, file: None }).is_err());
+
+        let domains = FailureDomains::new(&DomainsConf { count: 3, file: None }).unwrap();
+        assert_eq!(domains.get_domain(4), Some(1));
+        assert_eq!(domains.get_processes(2, 10), vec![2, 5, 8]);
+
+        let domains = FailureDomains::new(&DomainsConf { count: 6, file: Some("config/test/process-sites.txt".to_string()) }).unwrap();
+        assert_eq!((0..8).map(|id| domains.get_domain(id).unwrap()).collect::<Vec<_>>(), vec![3, 3, 5, 3, 1, 5, 0, 1]);
+        assert_eq!(domains.get_processes(3, 8), vec![0, 1, 3]);
+        assert!(FailureDomains::new(&DomainsConf { count: 4, file: Some("config/test/process-sites.txt".to_string()) }).is_err());
+    }
+}
@@ -15,12 +15,62 @@ use self::asynchrony::NoAsynchrony;
 
 pub mod utils;
 
+pub mod builder;
+pub use self::builder::SimulationBuilder;
+
+pub mod observer;
+use self::observer::{SimulationObserver, Observers};
+
+pub mod time;
+pub use self::time::TimeUnit;
+
+pub mod selection;
+pub use self::selection::ProcessSelection;
+
+pub mod partition;
+use self::partition::Partition;
+
+pub mod bandwidth;
+use self::bandwidth::Bandwidth;
+pub use self::bandwidth::BandwidthConf;
+
+pub mod overrides;
+use self::overrides::NetworkOverrides;
+pub use self::overrides::NetworkOverride;
+
+pub mod profiles;
+use self::profiles::{AsyncProfiles, ProfileAssignment};
+pub use self::profiles::AsyncProfile;
+
+pub mod rpc;
+use self::rpc::{RequestId, RequestContext, PendingRequest, RpcRequest, RpcReply, RpcTimeout};
+
+pub mod detector;
+use self::detector::FailureDetector;
+pub use self::detector::FailureDetectorConf;
+
+pub mod sessions;
+use self::sessions::SessionGenerator;
+
+pub mod trace;
+use self::trace::TraceConf;
+
+pub mod domains;
+use self::domains::FailureDomains;
+pub use self::domains::DomainsConf;
+
+pub mod clock;
+use self::clock::Clock;
+pub use self::clock::ClockConf;
+
 mod wrappers;
 
 use self::binary_heap_plus::*;
 
-use yaml_rust::YamlLoader;
+use yaml_rust::{YamlLoader, YamlEmitter};
 use yaml_rust::yaml;
+use yaml_rust::parser::{Parser, MarkedEventReceiver, Event as YamlEvent};
+use yaml_rust::scanner::Marker;
 
 use std::fmt;
 use std::fmt::{Debug, Display};
@@ -28,7 +78,8 @@ use std::cmp::Ordering;
 use std::any::Any;
 
 use std::rc::Rc;
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
+use std::collections::{BTreeMap, BTreeSet};
 
 use std::fs;
 use std::path::Path;
@@ -38,8 +89,11 @@ use rand_xorshift::XorShiftRng;
 
 use serde::{Serialize, Deserialize};
 
+use crate::Error;
+
 use std::sync::atomic::{self, AtomicBool};
-use std::sync::Arc;
+#[cfg(all(feature = "checkpointing_on_ctrlc", not(test)))]
+use std::sync::Once;
 
 
 static CHURN_OUTPUT_FILE: &str = "output/churn-plot/churn.dat";
@@ -57,6 +111,10 @@ pub struct Conf {
     pub asynchrony: Box<Asynchrony>,
     pub op_duration: Option<Time>,
     pub churn_file: Option<String>,
+    /// churn schedule given in memory, with the same format as the contents of a churn file;
+    /// when set it is used instead of churn_file
+    #[serde(skip)]
+    pub churn: Option<String>,
     #[serde(default = "default_seed")]
     pub seed: u64,
     pub save: Option<Time>,
@@ -65,7 +123,78 @@ pub struct Conf {
     #[serde(default = "default_save_filename")]
     pub save_filename: String,
     pub load: Option<String>,
-    pub new_seed: Option<u64>
+    pub new_seed: Option<u64>,
+    /// order in which events with the same timestamp are executed, which a load must keep from the snapshot
+    #[serde(default)]
+    pub tie_breaking: TieBreaking,
+    /// what one unit of Time stands for, milliseconds by default
+    #[serde(default)]
+    pub time_unit: TimeUnit,
+    /// uplink and downlink capacities of the processes, by default messages are sent and received instantly
+    pub bandwidth: Option<BandwidthConf>,
+    /// changes to the network for some processes or links, possibly during some time
+    #[serde(default)]
+    pub network_overrides: Vec<NetworkOverride>,
+    /// asynchrony and op_duration of some of the processes, that differ from asynchrony and op_duration
+    #[serde(default)]
+    pub async_profiles: Vec<AsyncProfile>,
+    /// offset and drift of the local clocks of the processes, by default every process reads the global time
+    pub clock: Option<ClockConf>,
+    /// failure detector used by the processes to monitor each other, see Process::monitor
+    pub failure_detector: Option<FailureDetectorConf>,
+    /// forbids the processes to read the global membership, with Process::get_global_view and Process::is_process_up
+    #[serde(default)]
+    pub strict: bool,
+    /// processes returned by Process::get_introducer, by default any process that is up
+    #[serde(default)]
+    pub introducers: Vec<ProcessId>,
+    /// failure domains of the processes, used by the churn actions fail-domain, leave-domain and recover-domain
+    pub domains: Option<DomainsConf>
+}
+
+/// Order in which events with the same timestamp are executed.
+/// Events of kind "end" are always executed after the other events with the same timestamp.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TieBreaking {
+    /// by the order in which the events were scheduled
+    #[default]
+    Fifo,
+    /// the most recently scheduled event first
+    Lifo,
+    /// in a random order, that depends only on the given seed and on the order in which the events were scheduled
+    Random(u64)
+}
+
+
+impl Conf {
+    /// configuration for n processes with the same defaults used when parsing a configuration file
+    pub fn new(n: ProcessId) -> Self {
+        Conf {
+            n,
+            network: Default::default(),
+            asynchrony: Default::default(),
+            op_duration: None,
+            churn_file: None,
+            churn: None,
+            seed: default_seed(),
+            save: None,
+            save_and_stop: default_save_and_stop(),
+            save_filename: default_save_filename(),
+            load: None,
+            new_seed: None,
+            tie_breaking: TieBreaking::default(),
+            time_unit: TimeUnit::default(),
+            bandwidth: None,
+            network_overrides: Vec::new(),
+            async_profiles: Vec::new(),
+            clock: None,
+            failure_detector: None,
+            strict: false,
+            introducers: Vec::new(),
+            domains: None
+        }
+    }
 }
 
 fn default_seed() -> u64 { 0 }
@@ -84,6 +213,12 @@ pub struct Process {
     generation: u16,
     asynchrony: Rc<RefCell<Box<dyn Asynchrony>>>,
     op_duration: Time,
+    /// async profile of the process, None when it has the asynchrony and op_duration of the configuration
+    #[cfg_attr(feature = "checkpointing", serde(default))]
+    profile: Option<String>,
+    /// local clock, through which the process reads the time and schedules its calls
+    #[cfg_attr(feature = "checkpointing", serde(default))]
+    clock: Clock,
     network: Rc<RefCell<Box<dyn Network>>>,
     #[cfg_attr(feature = "checkpointing", serde(skip, default = "rng_default"))]
     rng: Rc<RefCell<XorShiftRng>>,
@@ -91,7 +226,42 @@ pub struct Process {
     queue: Rc<RefCell<EventQueue>>,
     #[cfg_attr(feature = "checkpointing", serde(skip, default = "default_processes"))]
     processes: Rc<RefCell<Vec<ProcessState>>>,
-    simulation_stops: bool
+    #[cfg_attr(feature = "checkpointing", serde(skip, default = "observer::default_observers"))]
+    observers: Observers,
+    #[cfg_attr(feature = "checkpointing", serde(skip, default = "default_partition"))]
+    partition: Rc<RefCell<Partition>>,
+    #[cfg_attr(feature = "checkpointing", serde(skip, default = "default_bandwidth"))]
+    bandwidth: Rc<RefCell<Bandwidth>>,
+    #[cfg_attr(feature = "checkpointing", serde(skip, default = "default_overrides"))]
+    overrides: Rc<RefCell<NetworkOverrides>>,
+    /// id of the next timer, returned by call and periodic
+    #[cfg_attr(feature = "checkpointing", serde(default))]
+    next_timer: Cell<u64>,
+    /// timers that have not executed for the last time nor were cancelled,
+    /// the local events of the other timers are skipped when they are due
+    #[cfg_attr(feature = "checkpointing", serde(default))]
+    timers: RefCell<BTreeSet<TimerId>>,
+    /// id of the next request, returned by request
+    #[cfg_attr(feature = "checkpointing", serde(default))]
+    next_request: Cell<u64>,
+    /// requests of the process that are waiting for their reply or timeout
+    #[cfg_attr(feature = "checkpointing", serde(default))]
+    requests: RefCell<BTreeMap<RequestId, PendingRequest>>,
+    /// set while the process handles a request
+    #[cfg_attr(feature = "checkpointing", serde(skip))]
+    request: Cell<Option<RequestContext>>,
+    /// processes monitored by the process and which of them it suspects
+    #[cfg_attr(feature = "checkpointing", serde(default))]
+    detector: RefCell<FailureDetector>,
+    /// set while the process handles a message corrupted by the network
+    #[cfg_attr(feature = "checkpointing", serde(skip))]
+    corrupted: Cell<bool>,
+    simulation_stops: bool,
+    /// read from the configuration, also after a load
+    #[cfg_attr(feature = "checkpointing", serde(skip))]
+    strict: bool,
+    #[cfg_attr(feature = "checkpointing", serde(skip))]
+    introducers: Vec<ProcessId>
 }
 
 fn default_queue() -> Rc<RefCell<EventQueue>> {
@@ -100,6 +270,35 @@ fn default_queue() -> Rc<RefCell<EventQueue>> {
 fn default_processes() -> Rc<RefCell<Vec<ProcessState>>> {
     Rc::new(RefCell::new(Vec::new()))
 }
+fn default_partition() -> Rc<RefCell<Partition>> {
+    Rc::new(RefCell::new(Partition::default()))
+}
+fn default_bandwidth() -> Rc<RefCell<Bandwidth>> {
+    Rc::new(RefCell::new(Bandwidth::default()))
+}
+fn default_overrides() -> Rc<RefCell<NetworkOverrides>> {
+    Rc::new(RefCell::new(NetworkOverrides::default()))
+}
+
+/// State of the kernel that every process shares, which is not saved with the processes
+/// but attached to them again after a load, see Process::attach
+#[derive(Clone)]
+struct KernelHandles {
+    current_ts: Rc<RefCell<Time>>,
+    rng: Rc<RefCell<XorShiftRng>>,
+    queue: Rc<RefCell<EventQueue>>,
+    processes: Rc<RefCell<Vec<ProcessState>>>,
+    observers: Observers,
+    partition: Rc<RefCell<Partition>>,
+    bandwidth: Rc<RefCell<Bandwidth>>,
+    overrides: Rc<RefCell<NetworkOverrides>>
+}
+
+impl Default for KernelHandles {
+    fn default() -> Self {
+        KernelHandles { current_ts: Rc::new(RefCell::new(0)), rng: rng_default(), queue: default_queue(), processes: default_processes(), observers: observer::default_observers(), partition: default_partition(), bandwidth: default_bandwidth(), overrides: default_overrides() }
+    }
+}
 
 impl Debug for Process {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
@@ -111,6 +310,9 @@ impl Debug for Process {
 #[derive(Debug)]
 struct ProcessState {
     up: bool,
+    /// the process left permanently, see SimulationKernel::remove_process
+    #[cfg_attr(feature = "checkpointing", serde(default))]
+    removed: bool,
     process: Rc<RefCell<Process>>
 }
 
@@ -123,9 +325,64 @@ pub struct SimulationKernel {
     queue: Rc<RefCell<EventQueue>>,
     current_ts: Rc<RefCell<Time>>,
     #[cfg_attr(all(feature = "checkpointing", not(feature = "rng_serde1")), serde(skip, default = "rng_default"))]
-    rng: Rc<RefCell<XorShiftRng>>
+    rng: Rc<RefCell<XorShiftRng>>,
+    #[cfg_attr(feature = "checkpointing", serde(skip, default = "observer::default_observers"))]
+    observers: Observers,
+    partition: Rc<RefCell<Partition>>,
+    bandwidth: Rc<RefCell<Bandwidth>>,
+    overrides: Rc<RefCell<NetworkOverrides>>,
+    /// session churn generators, by the index of their entry in the churn
+    sessions: BTreeMap<usize, SessionGenerator>,
+    /// read from the configuration, also after a load
+    #[cfg_attr(feature = "checkpointing", serde(skip))]
+    async_profiles: AsyncProfiles,
+    /// read from the configuration, also after a load
+    #[cfg_attr(feature = "checkpointing", serde(skip))]
+    domains: FailureDomains,
+    /// creates the applications of spawned processes, given again after a load
+    #[cfg_attr(feature = "checkpointing", serde(skip))]
+    factory: Option<ApplicationFactory>,
+    /// given to spawned processes, see Process::set_simulation_stops
+    #[cfg_attr(feature = "checkpointing", serde(skip))]
+    simulation_stops: bool,
+    #[cfg_attr(feature = "checkpointing", serde(skip, default = "run_state_default"))]
+    state: RunState,
+    #[cfg_attr(feature = "checkpointing", serde(skip))]
+    events_processed: u64
+}
+
+/// State of the simulation, returned after executing events with step, run_until, run_for or run
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum RunState {
+    /// there are still events to execute
+    Running,
+    /// reached the end event of the churn
+    End,
+    /// saved the snapshot and stopped, as requested by save_and_stop in the configuration
+    SaveAndStop,
+    /// the event queue is empty
+    Empty,
+    /// received a ctrl-c and saved a snapshot
+    Interrupted
+}
+
+impl RunState {
+    /// true when no more events are going to be executed
+    pub fn is_finished(&self) -> bool {
+        *self != RunState::Running
+    }
+}
+
+#[cfg(feature = "checkpointing")]
+fn run_state_default() -> RunState {
+    RunState::Running
 }
 
+/// set by the ctrl-c handler, which is installed only once per program
+static CTRLC_RECEIVED: AtomicBool = AtomicBool::new(false);
+#[cfg(all(feature = "checkpointing_on_ctrlc", not(test)))]
+static CTRLC_HANDLER: Once = Once::new();
+
 #[cfg(any(feature = "checkpointing", not(feature = "rng_serde1")))]
 fn rng_default() -> Rc<RefCell<XorShiftRng>> {
     Rc::new(RefCell::new(XorShiftRng::seed_from_u64(0)))
@@ -133,7 +390,7 @@ fn rng_default() -> Rc<RefCell<XorShiftRng>> {
 
 impl Default for SimulationKernel {
     fn default() -> Self {
-        SimulationKernel { apps: Vec::new(), processes: Rc::new(RefCell::new(Vec::new())), queue: Rc::new(RefCell::new(EventQueue::default())), current_ts: Rc::new(RefCell::new(0)), rng: Rc::new(RefCell::new(XorShiftRng::seed_from_u64(0))) }
+        SimulationKernel { apps: Vec::new(), processes: Rc::new(RefCell::new(Vec::new())), queue: Rc::new(RefCell::new(EventQueue::default())), current_ts: Rc::new(RefCell::new(0)), rng: Rc::new(RefCell::new(XorShiftRng::seed_from_u64(0))), observers: observer::default_observers(), partition: default_partition(), bandwidth: default_bandwidth(), overrides: default_overrides(), sessions: BTreeMap::new(), async_profiles: AsyncProfiles::default(), domains: FailureDomains::default(), factory: None, simulation_stops: false, state: RunState::Running, events_processed: 0 }
     }
 }
 
@@ -141,24 +398,52 @@ impl Default for SimulationKernel {
 #[derive(Debug)]
 pub struct EventQueue {
     heap : BinaryHeap<Event, MinComparator>,
+    tie_breaking: TieBreaking,
+    /// number of events added so far, used to order events with the same timestamp
+    added: u64
 }
 
 impl EventQueue {
     fn new() -> EventQueue {
-        EventQueue { heap: BinaryHeap::new_min() }
+        EventQueue::with_tie_breaking(TieBreaking::default())
+    }
+    fn with_tie_breaking(tie_breaking: TieBreaking) -> EventQueue {
+        EventQueue { heap: BinaryHeap::new_min(), tie_breaking, added: 0 }
     }
     fn len(&self) -> usize {
         self.heap.len()
     }
-    fn add_event(&mut self, event: Event) {
+    fn next_seq(&mut self) -> u64 {
+        let seq = match self.tie_breaking {
+            TieBreaking::Fifo => self.added,
+            TieBreaking::Lifo => u64::MAX - self.added,
+            TieBreaking::Random(seed) => utils::mix64(seed ^ utils::mix64(self.added))
+        };
+        self.added += 1;
+        seq
+    }
+    fn add_event(&mut self, mut event: Event) {
+        event.seq = self.next_seq();
         self.heap.push(event);
     }
     fn add_events(&mut self, events: Vec<Event>) {
-        self.heap.extend(events);
+        for event in events {
+            self.add_event(event);
+        }
     }
     fn next_event(&mut self) -> Option<Event> {
         self.heap.pop()
     }
+    fn next_ts(&self) -> Option<Time> {
+        self.heap.peek().map(|e| e.ts)
+    }
+    /// number of processes that the spawn churn actions in the queue add
+    fn spawns(&self) -> ProcessId {
+        self.heap.iter().map(|e| match &e.kind {
+            EventKind::Churn(ChurnKind::Spawn { num_proc }) => *num_proc,
+            _ => 0
+        }).sum()
+    }
 }
 
 impl Default for EventQueue {
@@ -167,7 +452,11 @@ impl Default for EventQueue {
     }
 }
 
-pub type Time = i32;
+pub type Time = i64;
+
+/// Identifies a call or periodic call of a process, to cancel it with Process::cancel
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Serialize, Deserialize)]
+pub struct TimerId(u64);
 
 // Event structure. Events have a timestamp, a target process and the operation to be invoked
 #[cfg_attr(feature = "checkpointing", derive(Serialize, Deserialize))]
@@ -175,26 +464,37 @@ pub type Time = i32;
 pub struct Event {
     ts: Time,
     target: ProcessId,
-    op: Box<Operation>,
-    kind: EventKind
+    /// shared by the copies of a message duplicated by the network
+    op: Rc<dyn Operation>,
+    kind: EventKind,
+    /// the message was corrupted by the network
+    corrupted: bool,
+    /// sender of a message
+    #[cfg_attr(feature = "checkpointing", serde(default))]
+    from: ProcessId,
+    /// orders events with the same timestamp, assigned by the EventQueue when the event is added
+    seq: u64
 }
 
 impl Event {
+    fn new(ts: Time, target: ProcessId, op: Rc<dyn Operation>, kind: EventKind) -> Event {
+        Event { ts, target, op, kind, corrupted: false, from: 0, seq: 0 }
+    }
     fn new_churn(ts: Time, churn_kind: ChurnKind) -> Event {
-        Event { ts, target: 0, op: Box::new(Kernel), kind: EventKind::Churn(churn_kind) }
+        Event::new(ts, 0, Rc::new(Kernel), EventKind::Churn(churn_kind))
     }
     fn new_save(ts: Time) -> Event {
-        Event { ts, target: 0, op: Box::new(Kernel), kind: EventKind::Save }
+        Event::new(ts, 0, Rc::new(Kernel), EventKind::Save)
     }
     fn new_end(ts: Time) -> Event {
-        Event { ts, target: 0, op: Box::new(Kernel), kind: EventKind::Churn(ChurnKind::End) }
+        Event::new(ts, 0, Rc::new(Kernel), EventKind::Churn(ChurnKind::End))
     }
 }
 
 #[cfg_attr(feature = "checkpointing", derive(Serialize, Deserialize))]
 #[derive(PartialEq, Eq, Debug)]
 enum EventKind {
-    Local(u16, Time, u16), // (generation , delta, count)
+    Local(u16, Time, u16, TimerId), // (generation , delta, count, timer)
     Message,
     Churn(ChurnKind),
     Save
@@ -216,12 +516,39 @@ enum ChurnKind {
     Fail { num_proc: ProcessId },
     /// processes can rejoin, i.e. become "alive" again
     Recover { num_proc: ProcessId },
+    /// equivalent to Join but for a specific process, which joins only if it did not join before
+    JoinId { id: ProcessId },
     /// equivalent to Leave but for a specific process
     LeaveId { id: ProcessId },
+    /// process id leaves permanently, so its memory is reclaimed and it can no longer recover
+    RemoveId { id: ProcessId },
+    /// new processes, with the ids that follow the existing ones, are created by the application factory and join
+    Spawn { num_proc: ProcessId },
     /// equivalent to Fail but for a specific process
     FailId { id: ProcessId },
     /// equivalent to Recover but for a specific process
     RecoverId { id: ProcessId },
+    /// splits the processes into groups that are unable to communicate with each other,
+    /// the processes that are not in any group form one more group
+    Partition { groups: Vec<Vec<ProcessId>> },
+    /// ends the partition, so that all processes can communicate again
+    Heal,
+    /// the network override with name starts to apply
+    Override { name: String },
+    /// the network override with name stops to apply
+    OverrideEnd { name: String },
+    /// the processes ids get the async profile with name
+    Profile { name: String, ids: Vec<ProcessId> },
+    /// process id starts an up session (recovers) or a down session (fails, or leaves),
+    /// of the session generator of the churn entry
+    Session { entry: usize, id: ProcessId, up: bool, leave: bool },
+    /// the processes of the failure domains fail, either of the given domains
+    /// or of num_domains domains taken at random, when no domains are given
+    FailDomain { domains: Vec<ProcessId>, num_domains: ProcessId },
+    /// equivalent to FailDomain but the processes leave
+    LeaveDomain { domains: Vec<ProcessId>, num_domains: ProcessId },
+    /// equivalent to FailDomain but the processes recover
+    RecoverDomain { domains: Vec<ProcessId>, num_domains: ProcessId },
     /// to end the simulation
     End
 }
@@ -229,7 +556,7 @@ enum ChurnKind {
 impl ToString for EventKind {
     fn to_string(&self) -> String {
         match self {
-            EventKind::Local(_, _, _) => {
+            EventKind::Local(_, _, _, _) => {
                 "local".to_string()
             },
             EventKind::Message => {
@@ -248,10 +575,10 @@ impl ToString for EventKind {
 impl ToString for ChurnKind {
     fn to_string(&self) -> String {
         match self {
-            ChurnKind::Join { num_proc: _ } => {
+            ChurnKind::Join { num_proc: _ } | ChurnKind::JoinId { id: _ } | ChurnKind::Spawn { num_proc: _ } => {
             	"join".to_string()
             },
-            ChurnKind::Leave { num_proc: _ } | ChurnKind::LeaveId { id: _ }  => {
+            ChurnKind::Leave { num_proc: _ } | ChurnKind::LeaveId { id: _ } | ChurnKind::RemoveId { id: _ } => {
             	"leave".to_string()
             },
             ChurnKind::Fail { num_proc: _ } | ChurnKind::FailId { id: _ }  => {
@@ -260,6 +587,39 @@ impl ToString for ChurnKind {
             ChurnKind::Recover { num_proc: _ } | ChurnKind::RecoverId { id: _ } => {
             	"recover".to_string()
             }
+            ChurnKind::Partition { groups: _ } => {
+                "partition".to_string()
+            },
+            ChurnKind::Heal => {
+                "heal".to_string()
+            },
+            ChurnKind::Override { name: _ } => {
+                "override".to_string()
+            },
+            ChurnKind::OverrideEnd { name: _ } => {
+                "override-end".to_string()
+            },
+            ChurnKind::Profile { name: _, ids: _ } => {
+                "profile".to_string()
+            },
+            ChurnKind::Session { up: true, .. } => {
+                "recover".to_string()
+            },
+            ChurnKind::Session { up: false, leave: true, .. } => {
+                "leave".to_string()
+            },
+            ChurnKind::Session { up: false, leave: false, .. } => {
+                "fail".to_string()
+            },
+            ChurnKind::FailDomain { .. } => {
+                "fail".to_string()
+            },
+            ChurnKind::LeaveDomain { .. } => {
+                "leave".to_string()
+            },
+            ChurnKind::RecoverDomain { .. } => {
+                "recover".to_string()
+            },
             ChurnKind::End => {
                 "end".to_string()
             }
@@ -270,10 +630,10 @@ impl ToString for ChurnKind {
 impl ChurnKind {
     fn to_int(&self) -> i32 {
         match self {
-            ChurnKind::Join { num_proc: _ } => {
+            ChurnKind::Join { num_proc: _ } | ChurnKind::JoinId { id: _ } | ChurnKind::Spawn { num_proc: _ } => {
                 1
             },
-            ChurnKind::Leave { num_proc: _ } | ChurnKind::LeaveId { id: _ }  => {
+            ChurnKind::Leave { num_proc: _ } | ChurnKind::LeaveId { id: _ } | ChurnKind::RemoveId { id: _ } => {
                 -1
             },
             ChurnKind::Fail { num_proc: _ } | ChurnKind::FailId { id: _ }  => {
@@ -282,7 +642,16 @@ impl ChurnKind {
             ChurnKind::Recover { num_proc: _ } | ChurnKind::RecoverId { id: _ } => {
                 1
             },
-            ChurnKind::End => {
+            ChurnKind::Session { up, .. } => {
+                if *up { 1 } else { -1 }
+            },
+            ChurnKind::FailDomain { .. } | ChurnKind::LeaveDomain { .. } => {
+                -1
+            },
+            ChurnKind::RecoverDomain { .. } => {
+                1
+            },
+            ChurnKind::Partition { groups: _ } | ChurnKind::Heal | ChurnKind::Override { name: _ } | ChurnKind::OverrideEnd { name: _ } | ChurnKind::Profile { name: _, ids: _ } | ChurnKind::End => {
                 0
             }
         }
@@ -292,16 +661,11 @@ impl ChurnKind {
 
 impl Ord for Event {
     fn cmp(&self, other: &Event) -> Ordering {
-        let res = self.ts.cmp(&other.ts);
-        if res == Ordering::Equal {
-            // we want events that are of kind "end" to be the last executed compared to events with the same timestamp
-            if let EventKind::Churn(ChurnKind::End) = self.kind {
-                return Ordering::Greater;
-            } else if let EventKind::Churn(ChurnKind::End) = other.kind {
-                return Ordering::Less;
-            }
-        }
-        res
+        // we want events that are of kind "end" to be the last executed compared to events with the same timestamp
+        let is_end = |e: &Event| matches!(e.kind, EventKind::Churn(ChurnKind::End));
+        self.ts.cmp(&other.ts)
+            .then(is_end(self).cmp(&is_end(other)))
+            .then(self.seq.cmp(&other.seq))
     }
 }
 
@@ -313,13 +677,24 @@ impl PartialOrd for Event {
 
 impl PartialEq for Event {
     fn eq(&self, other: &Event) -> bool {
-        self.ts == other.ts
+        self.cmp(other) == Ordering::Equal
     }
 }
 
 #[cfg_attr(feature = "checkpointing", typetag::serde(tag = "type"))]
 pub trait Operation {
     fn invoke(&self, app_b: Rc<RefCell<Box<ApplicationBase>>>, process: Rc<RefCell<Process>>);
+
+    /// called when the network corrupts this message, to return the damaged message that is delivered instead
+    /// by default the message is delivered unchanged, and the receiver can only tell by Process::is_corrupted
+    fn corrupt(&self, _rng: &mut XorShiftRng) -> Option<Box<dyn Operation>> {
+        None
+    }
+
+    /// size in bytes of this message, which delays it when the bandwidth of the processes is limited
+    fn size(&self) -> u64 {
+        0
+    }
 }
 
 impl PartialEq for Operation {
@@ -351,6 +726,10 @@ pub trait ApplicationBase: Debug {
     fn leave(&mut self, _process: Rc<RefCell<Process>>);
     fn recover(&mut self, _process: Rc<RefCell<Process>>);
     fn on_load(&mut self, _process: Rc<RefCell<Process>>, _apps: &Vec<Rc<RefCell<Box<dyn ApplicationBase>>>>);
+    /// the failure detector of the process started to suspect that suspected failed, see Process::monitor
+    fn on_suspect(&mut self, _process: Rc<RefCell<Process>>, _suspected: ProcessId) {}
+    /// the failure detector of the process received a heartbeat of restored, which it suspected
+    fn on_restore(&mut self, _process: Rc<RefCell<Process>>, _restored: ProcessId) {}
 
     fn as_any(&self) -> &dyn Any;
     fn as_any_mut(&mut self) -> &mut dyn Any;
@@ -363,41 +742,165 @@ impl PartialEq for ApplicationBase {
 }
 impl Eq for ApplicationBase {}
 
+/// Creates the application of a process spawned by the spawn churn action, given the id of the process,
+/// see SimulationBuilder::application_factory
+#[derive(Clone)]
+pub struct ApplicationFactory(Rc<dyn Fn(ProcessId) -> Box<dyn ApplicationBase>>);
+
+impl ApplicationFactory {
+    pub fn new<F: Fn(ProcessId) -> Box<dyn ApplicationBase> + 'static>(factory: F) -> Self {
+        ApplicationFactory(Rc::new(factory))
+    }
+    pub fn create(&self, id: ProcessId) -> Box<dyn ApplicationBase> {
+        (self.0)(id)
+    }
+}
+
+impl Debug for ApplicationFactory {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "ApplicationFactory")
+    }
+}
+
+/// Takes the place of the application of a process removed by the remove-id churn action,
+/// so that the memory of the application is reclaimed
+#[cfg_attr(feature = "checkpointing", derive(Serialize, Deserialize))]
+#[derive(Debug)]
+struct Tombstone;
+
+#[cfg_attr(feature = "checkpointing", typetag::serde)]
+impl ApplicationBase for Tombstone {
+    fn init(&mut self, _: Rc<RefCell<Process>>) {}
+    fn leave(&mut self, _: Rc<RefCell<Process>>) {}
+    fn recover(&mut self, _: Rc<RefCell<Process>>) {}
+    fn on_load(&mut self, _: Rc<RefCell<Process>>, _: &Vec<Rc<RefCell<Box<dyn ApplicationBase>>>>) {}
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
 
 impl Process {
-    fn new(id: ProcessId, current_ts: Rc<RefCell<Time>>, op_duration: Time, asynchrony: Rc<RefCell<Box<Asynchrony>>>, network: Rc<RefCell<Box<Network>>>, rng: Rc<RefCell<XorShiftRng>>, queue: Rc<RefCell<EventQueue>>, processes: Rc<RefCell<Vec<ProcessState>>>) -> Rc<RefCell<Process>> {
-        let process = Process { id, current_ts, generation: 0, asynchrony, network, rng, queue, processes, op_duration, simulation_stops: false };
+    fn new(id: ProcessId, op_duration: Time, asynchrony: Rc<RefCell<Box<Asynchrony>>>, network: Rc<RefCell<Box<Network>>>, handles: &KernelHandles) -> Rc<RefCell<Process>> {
+        let KernelHandles { current_ts, rng, queue, processes, observers, partition, bandwidth, overrides } = handles.clone();
+        let process = Process { id, current_ts, generation: 0, asynchrony, network, rng, queue, processes, observers, partition, bandwidth, overrides, op_duration, profile: None, clock: Clock::default(), next_timer: Cell::new(0), timers: RefCell::new(BTreeSet::new()), next_request: Cell::new(0), requests: RefCell::new(BTreeMap::new()), request: Cell::new(None), detector: RefCell::new(FailureDetector::default()), corrupted: Cell::new(false), simulation_stops: false, strict: false, introducers: Vec::new() };
         Rc::new(RefCell::new(process))
     }
+    /// messages between processes separated by a partition are dropped when sent
+    /// the active network overrides that apply to the message may drop it or change its latency, see NetworkOverride
+    /// the network may also lose the message, or deliver it more than once, see Network::get_deliveries
+    /// with limited bandwidth the message is delivered only after it is sent and received, see Bandwidth
     pub fn send(&self, op: Box<Operation>, target: u32) {
-        match self.network.borrow_mut().get_latency(self.rng.clone(), self.id, target) {
-            None => return,
-            Some(latency) => {
-                let ts = *self.current_ts.borrow() + latency;
-                self.queue.borrow_mut().add_event(Event { ts, target, op, kind: EventKind::Message });
+        let now = *self.current_ts.borrow();
+        let size = op.size();
+        let sent_ts = self.bandwidth.borrow_mut().send(now, self.id, size);
+        if !self.partition.borrow().connected(self.id, target) {
+            observer::notify(&self.observers, |o| o.on_message_dropped(now, self.id, target, &*op));
+            return;
+        }
+        let effect = self.overrides.borrow().get_effect(self.id, target);
+        if let Some(effect) = effect {
+            if effect.loss > 0.0 && self.rng.borrow_mut().gen::<f64>() < effect.loss {
+                observer::notify(&self.observers, |o| o.on_message_dropped(now, self.id, target, &*op));
+                return;
+            }
+        }
+        let mut deliveries = self.network.borrow_mut().get_deliveries(self.rng.clone(), self.id, target);
+        if deliveries.is_empty() {
+            observer::notify(&self.observers, |o| o.on_message_dropped(now, self.id, target, &*op));
+            return;
+        }
+        if let Some(effect) = effect {
+            for delivery in &mut deliveries {
+                delivery.latency = effect.apply(delivery.latency);
+            }
+        }
+
+        let op: Rc<dyn Operation> = Rc::from(op);
+        for delivery in deliveries {
+            let ts = self.bandwidth.borrow_mut().receive(now, sent_ts + delivery.latency, target, size);
+            let mut delivered = op.clone();
+            if delivery.corrupted {
+                if let Some(corrupted) = op.corrupt(&mut self.rng.borrow_mut()) {
+                    delivered = Rc::from(corrupted);
+                }
             }
+            observer::notify(&self.observers, |o| o.on_message_sent(now, self.id, target, ts, &*delivered));
+            let mut event = Event::new(ts, target, delivered, EventKind::Message);
+            event.corrupted = delivery.corrupted;
+            event.from = self.id;
+            self.queue.borrow_mut().add_event(event);
         }
     }
-    /// delta is the time after which the method should execute
-    pub fn call(&self, op: Box<Operation>, delta: Time) {
-        self.periodic(op, delta, 1);
+    /// delta is the time after which the method should execute, as measured by the local clock of the process
+    /// returns the timer of the call, that can be cancelled until the call executes
+    pub fn call(&self, op: Box<Operation>, delta: Time) -> TimerId {
+        self.periodic(op, delta, 1)
     }
-    /// delta is the time after which the method should execute
+    /// delta is the time after which the method should execute, as measured by the local clock of the process
     /// count is the number of times to repeat the execution of the method
-    pub fn periodic(&self, op: Box<Operation>, delta: Time, count: u16) {
-        if !self.simulation_stops && count == 0 {
-            eprintln!("\n!!! Warning !!! - You are scheduling an infinite periodic local call. \nIt is mandatory to exist a stop/end event for the simulation to stop.\nThis stop/event can be 'end' in churn, or save_and_stop in config if there is a save");
-            ::std::process::exit(1);
-        }
-
-        let mut ts = *self.current_ts.borrow() + delta;
+    /// count 0 repeats forever, which requires a stop/end event for the simulation to stop,
+    /// otherwise SimulationKernel::run returns an error when the call is due
+    /// returns the timer of the periodic call, that can be cancelled until the last execution
+    pub fn periodic(&self, op: Box<Operation>, delta: Time, count: u16) -> TimerId {
+        let timer = TimerId(self.next_timer.get());
+        self.next_timer.set(timer.0 + 1);
+        self.timers.borrow_mut().insert(timer);
+
+        let mut ts = *self.current_ts.borrow() + self.clock.global_delta(delta);
         ts = self.ts_with_async(ts);
-        self.queue.borrow_mut().add_event(Event { ts, target: self.id, op, kind: EventKind::Local(self.generation, delta, count) });
+        self.queue.borrow_mut().add_event(Event::new(ts, self.id, Rc::from(op), EventKind::Local(self.generation, delta, count, timer)));
+        timer
+    }
+    /// stops the executions of timer that are still due, returns false if timer already executed for the last time
+    /// or was cancelled before
+    pub fn cancel(&self, timer: TimerId) -> bool {
+        self.timers.borrow_mut().remove(&timer)
+    }
+    /// sends op to target as a request, to which target replies with Process::reply
+    /// when the reply arrives the process executes the operation of the reply and then on_reply,
+    /// and if it does not arrive within timeout the process executes on_timeout instead
+    /// a reply that arrives after the timeout, or a second reply to the same request, is ignored
+    pub fn request(&self, op: Box<dyn Operation>, target: ProcessId, on_reply: Box<dyn Operation>, on_timeout: Box<dyn Operation>, timeout: Time) -> RequestId {
+        let id = RequestId(self.next_request.get());
+        self.next_request.set(id.0 + 1);
+
+        let timer = self.call(Box::new(RpcTimeout { id, op: on_timeout }), timeout);
+        self.requests.borrow_mut().insert(id, PendingRequest { timer, on_reply: Rc::from(on_reply) });
+        self.send(Box::new(RpcRequest { request: RequestContext { id, sender: self.id }, op }), target);
+        id
+    }
+    /// the request being handled, None when the process handles an operation that is not a request
+    pub fn get_request(&self) -> Option<RequestContext> {
+        self.request.get()
+    }
+    /// sends op as the reply to request
+    pub fn reply(&self, request: RequestContext, op: Box<dyn Operation>) {
+        self.send(Box::new(RpcReply { id: request.id, op }), request.sender);
+    }
+    /// whether request is still waiting for its reply or timeout
+    pub fn is_request_pending(&self, request: RequestId) -> bool {
+        self.requests.borrow().contains_key(&request)
+    }
+    /// whether timer is going to execute again
+    pub fn is_pending(&self, timer: TimerId) -> bool {
+        self.timers.borrow().contains(&timer)
     }
     fn receive(&self, event: Event, _conf: &Conf, app: Rc<RefCell<Box<ApplicationBase>>>, process: Rc<RefCell<Process>>) {
+        if let EventKind::Local(_, _, 1, timer) = event.kind {
+            self.timers.borrow_mut().remove(&timer);
+        }
+        self.corrupted.set(event.corrupted);
         event.op.invoke(app, process);
-        if let EventKind::Local(_, delta, count) = event.kind {
-            self.reschedule_periodic(event.op, delta, count);
+        self.corrupted.set(false);
+        // the operation may have cancelled its own timer
+        if let EventKind::Local(_, delta, count, timer) = event.kind {
+            if self.is_pending(timer) {
+                self.reschedule_periodic(event.op, delta, count, timer);
+            }
         }
 
         if cfg!(feature = "test_network") {
@@ -407,8 +910,21 @@ impl Process {
             }
         }
     }
+    /// time read by the local clock of the process, the global time is given by SimulationKernel::get_time
+    /// and is the time passed to the observers
     pub fn get_time(&self) -> Time {
-        *self.current_ts.borrow()
+        self.clock.local_time(*self.current_ts.borrow())
+    }
+    pub fn get_clock(&self) -> Clock {
+        self.clock
+    }
+    /// name of the async profile of the process, None when it has the asynchrony and op_duration of the configuration
+    pub fn get_profile(&self) -> Option<&str> {
+        self.profile.as_deref()
+    }
+    /// whether the message being handled was corrupted by the network
+    pub fn is_corrupted(&self) -> bool {
+        self.corrupted.get()
     }
     /// returns a random number in the range [0.0, 1.0) 
     pub fn get_random(&self) -> f64 {
@@ -421,7 +937,9 @@ impl Process {
         self.id
     }
     /// returns all the processes that are up
+    /// panics in strict mode, where processes can only learn about others by exchanging messages
     pub fn get_global_view(&self) -> Vec<Rc<RefCell<Process>>> {
+        self.check_not_strict("get_global_view");
         let mut v = Vec::new();
         for state in &*self.processes.borrow() {
             if state.up {
@@ -430,13 +948,32 @@ impl Process {
         }
         v
     }
+    /// panics in strict mode, where a process can use a failure detector instead, see Process::monitor
     pub fn is_process_up(&self, id: ProcessId) -> bool {
+        self.check_not_strict("is_process_up");
         self.processes.borrow()[id as usize].up
     }
+    /// a process other than this one to contact when joining, taken at random among the introducers of the configuration
+    /// that are up, or among all processes that are up when there are no introducers, which strict mode does not allow
+    /// returns None when there is no such process, for e.g. for the first process to join
+    pub fn get_introducer(&self) -> Option<ProcessId> {
+        let processes = self.processes.borrow();
+        let candidates: Vec<ProcessId> = if self.introducers.is_empty() {
+            (0..processes.len() as ProcessId).filter(|&id| processes[id as usize].up && id != self.id).collect()
+        } else {
+            self.introducers.iter().cloned().filter(|&id| processes.get(id as usize).is_some_and(|p| p.up) && id != self.id).collect()
+        };
+        candidates.choose(&mut *self.rng.borrow_mut()).cloned()
+    }
+    fn check_not_strict(&self, method: &str) {
+        if self.strict {
+            panic!("process {} called Process::{}, which reads the global membership and is unavailable in strict mode", self.id, method);
+        }
+    }
     pub fn set_simulation_stops(&mut self, simulation_stops: bool) {
         self.simulation_stops = simulation_stops;
     }
-    fn reschedule_periodic(&self, op: Box<Operation>, delta: Time, count: u16) {
+    fn reschedule_periodic(&self, op: Rc<dyn Operation>, delta: Time, count: u16, timer: TimerId) {
         if count == 1 {
             return;
         }
@@ -447,9 +984,9 @@ impl Process {
             count - 1
         };
 
-        let mut ts = *self.current_ts.borrow() + delta;
+        let mut ts = *self.current_ts.borrow() + self.clock.global_delta(delta);
         ts = self.ts_with_async(ts);
-        self.queue.borrow_mut().add_event(Event { ts, target: self.id, op, kind: EventKind::Local(self.generation, delta, new_count) });
+        self.queue.borrow_mut().add_event(Event::new(ts, self.id, op, EventKind::Local(self.generation, delta, new_count, timer)));
     }
     fn ts_with_async(&self, ts: Time) -> Time {
         let asynchrony = self.asynchrony.borrow_mut().get_async(self.rng.clone(), self.op_duration);
@@ -463,23 +1000,30 @@ impl Process {
     fn log_async(&self, ts: Time, id: ProcessId) {
         let asynchrony = format!("{} {}", ts, id);
 
-        utils::save_to_file(ASYNC_OUTPUT_FILE, asynchrony, true);
+        if let Err(e) = utils::save_to_file(ASYNC_OUTPUT_FILE, asynchrony, true) {
+            eprintln!("{}", e);
+        }
     }
     #[cfg(feature = "test_network")]
     fn log_time_received(&self, time: Time) {
-        utils::save_to_file(LATENCY_FILE, time.to_string(), true);
+        if let Err(e) = utils::save_to_file(LATENCY_FILE, time.to_string(), true) {
+            eprintln!("{}", e);
+        }
     }
     fn get_generation(&self) -> u16 {
         self.generation
     }
-    fn set_rng(&mut self, rng: Rc<RefCell<XorShiftRng>>) {
+    /// links the process to the state of the kernel, which is not saved with the process
+    fn attach(&mut self, handles: &KernelHandles) {
+        let KernelHandles { current_ts, rng, queue, processes, observers, partition, bandwidth, overrides } = handles.clone();
+        self.current_ts = current_ts;
         self.rng = rng;
-    }
-    fn set_processes(&mut self, processes: Rc<RefCell<Vec<ProcessState>>>) {
+        self.queue = queue;
         self.processes = processes;
-    }
-    fn set_current_ts(&mut self, current_ts: Rc<RefCell<Time>>) {
-        self.current_ts = current_ts;
+        self.observers = observers;
+        self.partition = partition;
+        self.bandwidth = bandwidth;
+        self.overrides = overrides;
     }
 }
 
@@ -488,93 +1032,85 @@ impl SimulationKernel {
         SimulationKernel {
             apps : Vec::with_capacity(conf.n as usize),
             processes : Rc::new(RefCell::new(Vec::with_capacity(conf.n as usize))),
-            queue: Rc::new(RefCell::new(EventQueue::new())),
+            queue: Rc::new(RefCell::new(EventQueue::with_tie_breaking(conf.tie_breaking))),
             current_ts: Rc::new(RefCell::new(0)),
-            rng: Rc::new(RefCell::new(XorShiftRng::seed_from_u64(conf.seed)))
+            rng: Rc::new(RefCell::new(XorShiftRng::seed_from_u64(conf.seed))),
+            observers: observer::default_observers(),
+            partition: default_partition(),
+            bandwidth: default_bandwidth(),
+            overrides: default_overrides(),
+            sessions: BTreeMap::new(),
+            async_profiles: AsyncProfiles::default(),
+            domains: FailureDomains::default(),
+            factory: None,
+            simulation_stops: false,
+            state: RunState::Running,
+            events_processed: 0
         }
     }
-    fn get_op_duration(conf: &Conf) -> Time {
+    fn get_op_duration(conf: &Conf) -> Result<Time, Error> {
         let no_async = conf.asynchrony.as_any().downcast_ref::<NoAsynchrony>();
 
-        if no_async.is_some() {
-            0
-        } else if conf.op_duration.is_none() {
-            eprintln!("Error: when asynchrony is enabled it is mandatory to provide op_duration in configuration file. ");
-            ::std::process::exit(-1)
-        } else {
-            conf.op_duration.unwrap()
+        match conf.op_duration {
+            _ if no_async.is_some() => Ok(0),
+            Some(op_duration) => Ok(op_duration),
+            None => Err(Error::Simulation("when asynchrony is enabled it is mandatory to provide op_duration in the configuration".to_string()))
         }
     }
-    pub fn init<P: AsRef<Path> + Display>(apps: &Vec<Rc<RefCell<Box<dyn ApplicationBase>>>>, conf_filename: P) -> Self {
-        let conf: Conf = utils::yaml_from_file_to_object(&conf_filename);
-
-        let mut kernel;
-        if let Some(_) = &conf.load {
-            kernel = SimulationKernel::load_state(&conf);
-
-            if let Some(seed) = conf.new_seed {
-                println!("After load, running with new seed {}", seed);
-            } else {
-                println!("After load, running with same Random Number Generator");
-            }
-        } else {
-            if conf.n as usize != apps.len() {
-                eprintln!("Error: in SimulationKernel::init, must receive a Vec of applications with size equal to n={}, as it is specified in the configuration file {}, but received {}", conf.n, conf_filename, apps.len());
-                ::std::process::exit(-1);
-            }
-
-            let op_duration = SimulationKernel::get_op_duration(&conf);
-
-            kernel = SimulationKernel::new(&conf);
-
-            let asynchrony = Rc::new(RefCell::new(conf.asynchrony.clone()));
-            let network = Rc::new(RefCell::new(conf.network.clone()));
-            //init process state
-            for i in 0..conf.n {
-                kernel.add_process(i as ProcessId, apps[i as usize].clone(), op_duration, asynchrony.clone(), network.clone());
-            }
-
-            println!("Running with seed {}", conf.seed);
-        }
-
-        let simulation_stops = kernel.config(&conf);
+    /// seed of the processes taken at random by churn entry, so that they do not depend on the Random Number Generator of the simulation
+    fn get_entry_seed(conf: &Conf, entry: usize) -> u64 {
+        utils::mix64(conf.seed ^ utils::mix64(!(entry as u64)))
+    }
+    pub fn init<P: AsRef<Path> + Display>(apps: &Vec<Rc<RefCell<Box<dyn ApplicationBase>>>>, conf_filename: P) -> Result<Self, Error> {
+        let builder = SimulationBuilder::from_file(conf_filename)?;
 
-        kernel.update_process_simulation_stops(simulation_stops);
+        let mut kernel = builder.build(apps)?;
 
-        kernel.run(&conf);
+        kernel.run(builder.conf())?;
 
-        kernel
+        Ok(kernel)
     }
     fn update_kernel(kernel: &mut Self, conf: &Conf) {
         if kernel.processes.borrow().len() > 0 {
             let p0 = kernel.get_process(0).unwrap();
-            let asynchrony = p0.borrow().asynchrony.clone();
             let network = p0.borrow().network.clone();
+            // processes with an async profile keep their own asynchrony
+            let asynchrony = kernel.processes.borrow().iter()
+                .find(|p| p.process.borrow().profile.is_none())
+                .map(|p| p.process.borrow().asynchrony.clone());
 
             if let Some(seed) = conf.new_seed {
                 kernel.rng = Rc::new(RefCell::new(XorShiftRng::seed_from_u64(seed)));
             }
 
+            let handles = kernel.get_handles();
             for i in 0..kernel.processes.borrow().len() {
                 let process = kernel.get_process(i as ProcessId).unwrap();
                 let mut p = process.borrow_mut();
-                p.queue = kernel.queue.clone();
-                p.set_rng(kernel.rng.clone());
-                p.set_processes(kernel.processes.clone());
-                p.set_current_ts(kernel.current_ts.clone());
+                p.attach(&handles);
                 if i != 0 {
-                    p.asynchrony = asynchrony.clone();
                     p.network = network.clone();
                 }
+                if let (Some(asynchrony), None) = (&asynchrony, &p.profile) {
+                    p.asynchrony = asynchrony.clone();
+                }
             }
         }
     }
     #[cfg(feature = "checkpointing")]
-    pub fn load_state(conf: &Conf) -> Self {
-        let load_filename = conf.load.as_ref().unwrap();
+    pub fn load_state(conf: &Conf) -> Result<Self, Error> {
+        let load_filename = match &conf.load {
+            Some(load_filename) => load_filename,
+            None => return Err(Error::Simulation("unable to load state, no load file in the configuration".to_string()))
+        };
         println!("Loading state from {}", load_filename);
 
-        let mut kernel: SimulationKernel = utils::binary_from_file_to_object(load_filename);
+        let mut kernel: SimulationKernel = utils::binary_from_file_to_object(load_filename)?;
+        // the events in the queue already have their order, which another tie_breaking would not follow
+        let tie_breaking = kernel.queue.borrow().tie_breaking;
+        if tie_breaking != conf.tie_breaking {
+            return Err(Error::Simulation(format!("{} was saved with tie_breaking {:?}, which the configuration must keep, but it has {:?}", load_filename, tie_breaking, conf.tie_breaking)));
+        }
         SimulationKernel::update_kernel(&mut kernel, &conf);
 
         for i in 0..kernel.apps.len() {
@@ -583,21 +1119,40 @@ impl SimulationKernel {
             app.on_load(process, &kernel.apps);
         }
 
-        kernel
+        Ok(kernel)
     }
     #[cfg(not(feature = "checkpointing"))]
-    pub fn load_state(_conf: &Conf) -> Self {
-        eprintln!("Unable to load state, due to the checkpointing feature being disabled. ");
-        ::std::process::exit(-1)
+    pub fn load_state(_conf: &Conf) -> Result<Self, Error> {
+        Err(Error::Simulation("unable to load state, due to the checkpointing feature being disabled".to_string()))
+    }
+    /// the observer is notified of everything the kernel does from now on, including by the processes
+    pub fn add_observer(&mut self, observer: Rc<RefCell<dyn SimulationObserver>>) {
+        self.observers.borrow_mut().push(observer);
     }
-    pub fn update_process_simulation_stops(&self, simulation_stops: bool) {
+    pub fn update_process_simulation_stops(&mut self, simulation_stops: bool) {
+        self.simulation_stops = simulation_stops;
         for p in &*self.processes.borrow() {
             p.process.borrow_mut().set_simulation_stops(simulation_stops);
         }
     }
+    /// creates the applications of the processes spawned by the spawn churn action
+    pub fn set_application_factory(&mut self, factory: ApplicationFactory) {
+        self.factory = Some(factory);
+    }
+    /// strict mode and introducers of the processes, see Conf::strict and Conf::introducers
+    pub fn update_process_strict(&self, strict: bool, introducers: &[ProcessId]) {
+        for p in &*self.processes.borrow() {
+            let mut process = p.process.borrow_mut();
+            process.strict = strict;
+            process.introducers = introducers.to_vec();
+        }
+    }
+    fn get_handles(&self) -> KernelHandles {
+        KernelHandles { current_ts: self.current_ts.clone(), rng: self.rng.clone(), queue: self.queue.clone(), processes: self.processes.clone(), observers: self.observers.clone(), partition: self.partition.clone(), bandwidth: self.bandwidth.clone(), overrides: self.overrides.clone() }
+    }
     pub fn add_process(&mut self, id: ProcessId, app: Rc<RefCell<Box<dyn ApplicationBase>>>, op_duration: Time, asynchrony: Rc<RefCell<Box<Asynchrony>>>, network: Rc<RefCell<Box<Network>>>) {
-        let process: Rc<RefCell<Process>> = Process::new(id, self.current_ts.clone(), op_duration, asynchrony, network, self.rng.clone(),self.queue.clone(), self.processes.clone());
-        self.processes.borrow_mut().push(ProcessState { up: false, process });
+        let process: Rc<RefCell<Process>> = Process::new(id, op_duration, asynchrony, network, &self.get_handles());
+        self.processes.borrow_mut().push(ProcessState { up: false, removed: false, process });
         self.apps.push(app);
     }
     fn add_event(&mut self, event: Event) {
@@ -624,6 +1179,13 @@ impl SimulationKernel {
             false
         }
     }
+    fn is_process_removed(&self, id: ProcessId) -> bool {
+        if self.id_in_use(id) {
+            self.processes.borrow()[id as usize].removed
+        } else {
+            false
+        }
+    }
     fn get_process(&self, id: ProcessId) -> Option<Rc<RefCell<Process>>> {
         if self.id_in_use(id) {
             Some(self.processes.borrow()[id as usize].process.clone())
@@ -652,7 +1214,7 @@ impl SimulationKernel {
         let mut v = Vec::new();
         for process_state in &*self.processes.borrow() {
             let id = process_state.process.borrow().id;
-            if !self.is_process_up(id) {
+            if !self.is_process_up(id) && !self.is_process_removed(id) {
                 if self.has_process_joined(id) {
                     v.push(id);
                 }
@@ -664,7 +1226,7 @@ impl SimulationKernel {
         let mut v = Vec::new();
         for process_state in &*self.processes.borrow() {
             let id = process_state.process.borrow().id;
-            if !self.is_process_up(id) {
+            if !self.is_process_up(id) && !self.is_process_removed(id) {
                 if !self.has_process_joined(id) {
                     v.push(id);
                 }
@@ -690,6 +1252,10 @@ impl SimulationKernel {
         }
         v
     }
+    /// failure domains of the processes, which are empty unless given in the configuration
+    pub fn get_failure_domains(&self) -> &FailureDomains {
+        &self.domains
+    }
     pub fn get_processes(&self) -> Vec<Rc<RefCell<Process>>> {
         let mut v = Vec::new();
         for process_state in &*self.processes.borrow() {
@@ -703,12 +1269,28 @@ impl SimulationKernel {
     fn get_random_from_vec(&mut self, processes_ids: &mut Vec<ProcessId>, num_proc: ProcessId) -> Vec<ProcessId> {
         processes_ids.choose_multiple(&mut *self.rng.borrow_mut(), num_proc as usize).cloned().collect()
     }
+    /// the processes for which affected is true in the domains, or in num_domains domains taken at random among
+    /// those with such processes, when no domains are given
+    fn get_domains_processes<F: Fn(&Self, ProcessId) -> bool>(&mut self, domains: &[ProcessId], num_domains: ProcessId, affected: F) -> Vec<ProcessId> {
+        let n = self.processes.borrow().len() as ProcessId;
+        let affected_in = |kernel: &Self, domain: ProcessId| kernel.domains.get_processes(domain, n).into_iter().filter(|&id| affected(kernel, id)).collect::<Vec<ProcessId>>();
+        let domains = if domains.is_empty() {
+            let mut candidates = (0..self.domains.len()).filter(|&domain| !affected_in(self, domain).is_empty()).collect();
+            self.get_random_from_vec(&mut candidates, num_domains)
+        } else {
+            domains.to_vec()
+        };
+        domains.iter().flat_map(|&domain| affected_in(self, domain)).collect()
+    }
     fn join_process(&mut self, ts: Time, id: ProcessId, _conf: &Conf) {
-        if self.id_in_use(id) {
+        if self.id_in_use(id) && !self.is_process_removed(id) {
             let p = self.get_process(id).unwrap();
             {
                 let mut process = p.borrow_mut();
                 process.generation = 1;
+                process.timers.borrow_mut().clear();
+                process.requests.borrow_mut().clear();
+                process.detector.borrow_mut().reset();
                 *process.current_ts.borrow_mut() = ts;
             }
             let app = self.get_application(id).unwrap();
@@ -718,6 +1300,9 @@ impl SimulationKernel {
         }
     }
     fn leave_process(&mut self, _ts: Time, id: ProcessId) {
+        if !self.id_in_use(id) {
+            return;
+        }
         let app = self.get_application(id).unwrap();
         let process = self.get_process(id).unwrap();
         app.borrow_mut().leave(process);
@@ -728,11 +1313,14 @@ impl SimulationKernel {
         self.set_process_status(id, false);
     }
     fn recover_process(&mut self, ts: Time, id: ProcessId, _conf: &Conf) {
-        if self.id_in_use(id) {
+        if self.id_in_use(id) && !self.is_process_removed(id) {
             let p = self.get_process(id).unwrap();
             {
                 let mut process = p.borrow_mut();
                 process.generation += 1;
+                process.timers.borrow_mut().clear();
+                process.requests.borrow_mut().clear();
+                process.detector.borrow_mut().reset();
                 *process.current_ts.borrow_mut() = ts;
             }
             let app = self.get_application(id).unwrap();
@@ -741,6 +1329,62 @@ impl SimulationKernel {
             self.set_process_status(id, true);
         }
     }
+    /// process id leaves permanently, and its application is replaced by a tombstone so that its memory is reclaimed
+    /// the process can no longer join or recover, and its id is not reused
+    fn remove_process(&mut self, ts: Time, id: ProcessId) {
+        if !self.id_in_use(id) || self.is_process_removed(id) {
+            return;
+        }
+        if self.is_process_up(id) {
+            self.leave_process(ts, id);
+        }
+        {
+            let p = self.get_process(id).unwrap();
+            let process = p.borrow();
+            process.timers.borrow_mut().clear();
+            process.requests.borrow_mut().clear();
+            process.detector.borrow_mut().reset();
+        }
+        self.apps[id as usize] = Rc::new(RefCell::new(Box::new(Tombstone)));
+        self.processes.borrow_mut()[id as usize].removed = true;
+    }
+    /// creates a process with the next id, whose application is given by the factory, and the process joins
+    /// it shares the network and the asynchrony of the other processes, and gets the clock and failure detector of the configuration
+    fn spawn_process(&mut self, ts: Time, conf: &Conf) -> Option<ProcessId> {
+        let factory = self.factory.clone()?;
+        let id = self.processes.borrow().len() as ProcessId;
+
+        let network = match self.get_process(0) {
+            Some(p0) => p0.borrow().network.clone(),
+            None => {
+                let mut network = conf.network.clone();
+                network.set_time_unit(conf.time_unit);
+                Rc::new(RefCell::new(network))
+            }
+        };
+        let template = self.processes.borrow().iter().map(|state| state.process.clone()).find(|p| p.borrow().profile.is_none());
+        let (asynchrony, op_duration) = match template {
+            Some(p) => (p.borrow().asynchrony.clone(), p.borrow().op_duration),
+            None => (Rc::new(RefCell::new(conf.asynchrony.clone())), SimulationKernel::get_op_duration(conf).expect("op_duration is checked when the spawn is configured"))
+        };
+
+        self.add_process(id, Rc::new(RefCell::new(factory.create(id))), op_duration, asynchrony, network);
+        {
+            let p = self.get_process(id).unwrap();
+            let mut process = p.borrow_mut();
+            process.set_simulation_stops(self.simulation_stops);
+            process.strict = conf.strict;
+            process.introducers = conf.introducers.clone();
+        }
+        if let Some(failure_detector) = &conf.failure_detector {
+            self.set_failure_detector(id, failure_detector.clone());
+        }
+        if let Some(clock) = &conf.clock {
+            self.set_clock(id, clock.get_clock(id, conf.seed).expect("the clocks of the spawned processes are checked when the churn is configured"));
+        }
+        self.join_process(ts, id, conf);
+        Some(id)
+    }
     fn recover_processes(&mut self, ts: Time, conf: &Conf, num_proc: ProcessId) -> Vec<ProcessId> {
         let mut ids_down = self.get_processes_ids_down();
         let ids = self.get_random_from_vec(&mut ids_down, num_proc);
@@ -785,9 +1429,15 @@ impl SimulationKernel {
     fn add_recover_event(&mut self, ts: Time, num_proc: ProcessId) {
         self.add_event(Event::new_churn(ts, ChurnKind::Recover { num_proc }));
     }
+    fn add_join_id_event(&mut self, ts: Time, id: ProcessId) {
+        self.add_event(Event::new_churn(ts, ChurnKind::JoinId { id }));
+    }
     fn add_leave_id_event(&mut self, ts: Time, id: ProcessId) {
         self.add_event(Event::new_churn(ts, ChurnKind::LeaveId { id }));
     }
+    fn add_remove_id_event(&mut self, ts: Time, id: ProcessId) {
+        self.add_event(Event::new_churn(ts, ChurnKind::RemoveId { id }));
+    }
     fn add_fail_id_event(&mut self, ts: Time, id: ProcessId) {
         self.add_event(Event::new_churn(ts, ChurnKind::FailId { id }));
     }
@@ -800,14 +1450,11 @@ impl SimulationKernel {
     fn add_save_event(&mut self, ts: Time) {
         self.add_event(Event::new_save(ts));
     }
-    fn handle_churn_num_proc(&mut self, ts: Time, action: &str, num: &yaml::Yaml, n: ProcessId) {
-        let num_int = num.as_i64();
-        let num_proc = match num_int {
-            Some(integer) => integer as u32,
-            None => {
-                let percent_proc = num.as_f64().expect("3rd param was not int, so it should be float but since you're seeing this it isn't a float either");
-                (percent_proc * n as f64).round() as u32
-            }
+    fn handle_churn_num_proc(&mut self, ts: Time, action: &str, num: &yaml::Yaml, n: ProcessId) -> Result<(), String> {
+        let num_proc = match (num.as_i64(), num.as_f64()) {
+            (Some(integer), _) if integer >= 0 => integer as ProcessId,
+            (None, Some(percent_proc)) if percent_proc >= 0.0 => (percent_proc * n as f64).round() as ProcessId,
+            _ => return Err(format!("{} expects a non-negative number of processes (integer) or a fraction of n (float) as 3rd parameter", action))
         };
 
         match action {
@@ -815,17 +1462,207 @@ impl SimulationKernel {
             "leave" => { self.add_leave_event(ts, num_proc); },
             "fail" => { self.add_fail_event(ts, num_proc); },
             "recover" => { self.add_recover_event(ts, num_proc); },
-            _ => { println!("expected join, leave, or recover"); }
+            _ => return Err(format!("expected join, leave, fail or recover but found {}", action))
+        }
+        Ok(())
+    }
+    /// ids is a process id, a list of ids or a range "first..last", whose events are scheduled in that order,
+    /// so with the fifo tie_breaking the processes join, leave, fail or recover in the order in which they are given
+    fn handle_churn_id(&mut self, time: Time, action: &str, ids: &yaml::Yaml, n: ProcessId) -> Result<(), String> {
+        let selection = match ids {
+            yaml::Yaml::Integer(_) | yaml::Yaml::Array(_) | yaml::Yaml::String(_) => ProcessSelection::from_yaml(ids, n).map_err(|e| format!("{}: {}", action, e))?,
+            _ => return Err(format!("{} expects a process id, a list of ids or a range \"first..last\" as 3rd parameter", action))
+        };
+
+        for id in selection.resolve(n, &mut 0, 0) {
+            match action {
+                "join-id" => { self.add_join_id_event(time, id); },
+                "leave-id" => { self.add_leave_id_event(time, id); },
+                "remove-id" => { self.add_remove_id_event(time, id); },
+                "fail-id" => { self.add_fail_id_event(time, id); },
+                "recover-id" => { self.add_recover_id_event(time, id); },
+                _ => return Err(format!("expected join-id, leave-id, remove-id, fail-id or recover-id but found {}", action))
+            }
         }
+        Ok(())
     }
-    fn handle_churn_id(&mut self, time: Time, action: &str, num: &yaml::Yaml) {
-        let id = num.as_i64().expect("id must be an integer") as ProcessId;
+    /// domains is a domain id, a list of domain ids, or a fraction of the domains, which are taken at random
+    fn handle_churn_domain(&mut self, time: Time, action: &str, domains: &yaml::Yaml) -> Result<(), String> {
+        let count = self.domains.len();
+        if count == 0 {
+            return Err(format!("{} requires domains in the configuration", action));
+        }
+        let check_domain = |domain: i64| {
+            if domain >= 0 && domain < count as i64 {
+                Ok(domain as ProcessId)
+            } else {
+                Err(format!("{} received domain {}, but domains must be in 0..{}", action, domain, count))
+            }
+        };
+        let usage = format!("{} expects a domain, a list of domains or a fraction of the domains (float) as 3rd parameter", action);
+        let (domains, num_domains) = match domains {
+            yaml::Yaml::Integer(domain) => (vec![check_domain(*domain)?], 0),
+            yaml::Yaml::Array(domains) => {
+                let domains = domains.iter()
+                    .map(|d| d.as_i64().ok_or_else(|| usage.clone()).and_then(check_domain))
+                    .collect::<Result<Vec<ProcessId>, String>>()?;
+                (domains, 0)
+            },
+            yaml::Yaml::Real(_) => match domains.as_f64() {
+                Some(fraction) if (0.0..=1.0).contains(&fraction) => (Vec::new(), (fraction * count as f64).round() as ProcessId),
+                _ => return Err(format!("{} expects a fraction of the domains between 0.0 and 1.0", action))
+            },
+            _ => return Err(usage)
+        };
 
-        match action {
-            "leave-id" => { self.add_leave_id_event(time, id); },
-            "fail-id" => { self.add_fail_id_event(time, id); },
-            "recover-id" => { self.add_recover_id_event(time, id); },
-            _ => { println!("expected leave-id, or recover-id"); }
+        let kind = match action {
+            "fail-domain" => ChurnKind::FailDomain { domains, num_domains },
+            "leave-domain" => ChurnKind::LeaveDomain { domains, num_domains },
+            "recover-domain" => ChurnKind::RecoverDomain { domains, num_domains },
+            _ => return Err(format!("expected fail-domain, leave-domain or recover-domain but found {}", action))
+        };
+        self.add_event(Event::new_churn(time, kind));
+        Ok(())
+    }
+    fn handle_churn_spawn(&mut self, time: Time, num: &yaml::Yaml, conf: &Conf) -> Result<(), String> {
+        if self.factory.is_none() {
+            return Err("spawn requires an application factory, see SimulationBuilder::application_factory".to_string());
+        }
+        // the spawned processes take op_duration from the configuration when there is no process to copy it from
+        SimulationKernel::get_op_duration(conf).map_err(|e| e.to_string())?;
+        let num_proc = match num.as_i64() {
+            Some(num_proc) if num_proc >= 0 => num_proc as ProcessId,
+            _ => return Err("spawn expects a non-negative number of processes (integer) as 3rd parameter".to_string())
+        };
+        self.add_event(Event::new_churn(time, ChurnKind::Spawn { num_proc }));
+        Ok(())
+    }
+    fn handle_churn_partition(&mut self, time: Time, groups: &yaml::Yaml, entry: usize, conf: &Conf) -> Result<(), String> {
+        let groups = groups.as_vec().ok_or_else(|| "partition expects a list of groups as 3rd parameter, where each group is an id, a list of ids, a range \"first..last\", a fraction of n, {random: fraction} or {probability: p}".to_string())?;
+        let selections = groups.iter().map(|g| ProcessSelection::from_yaml(g, conf.n)).collect::<Result<Vec<ProcessSelection>, String>>()?;
+        let groups = selection::resolve_groups(&selections, conf.n, SimulationKernel::get_entry_seed(conf, entry))?;
+
+        self.add_event(Event::new_churn(time, ChurnKind::Partition { groups }));
+        Ok(())
+    }
+    /// network_override is a mapping with the fields of NetworkOverride, which applies from time
+    fn handle_churn_override(&mut self, time: Time, network_override: &yaml::Yaml, entry: usize) -> Result<(), String> {
+        if network_override.as_hash().is_none() {
+            return Err("override expects a mapping with the fields of a network override as 3rd parameter, for e.g. {links: [[1, 2]], extra_latency: 200}".to_string());
+        }
+        let mut s = String::new();
+        YamlEmitter::new(&mut s).dump(network_override).map_err(|e| format!("{:?}", e))?;
+        let mut network_override: NetworkOverride = serde_yaml::from_str(&s).map_err(|e| format!("invalid network override: {}", e))?;
+        network_override.from = time;
+
+        let name = network_override.name.clone().unwrap_or_else(|| format!("churn-{}", entry));
+        self.schedule_override(name, network_override);
+        Ok(())
+    }
+    /// sessions is a mapping with the processes and the fields of a SessionGenerator
+    /// the generator is kept by the kernel, so after a load it is already running and the entry is skipped
+    /// without until the sessions never end, so the simulation must stop with an end event or save_and_stop
+    fn handle_churn_sessions(&mut self, time: Time, sessions: &yaml::Yaml, entry: usize, stops: bool, conf: &Conf) -> Result<(), String> {
+        if sessions.as_hash().is_none() {
+            return Err("sessions expects a mapping with the processes and the distributions of the up and down sessions as 3rd parameter, for e.g. {processes: \"0..50\", up: {exponential: {lambda: 0.001}}, down: {exponential: {lambda: 0.01}}}".to_string());
+        }
+        if self.sessions.contains_key(&entry) {
+            return Ok(());
+        }
+        let processes = match &sessions["processes"] {
+            yaml::Yaml::BadValue => ProcessSelection::Range(0, conf.n),
+            processes => ProcessSelection::from_yaml(processes, conf.n)?
+        };
+        let mut s = String::new();
+        YamlEmitter::new(&mut s).dump(sessions).map_err(|e| format!("{:?}", e))?;
+        let generator: SessionGenerator = serde_yaml::from_str(&s).map_err(|e| format!("invalid sessions: {}", e))?;
+        if generator.until.is_none() && !stops {
+            return Err("sessions without until never end, so the churn must have an end event or the configuration save_and_stop".to_string());
+        }
+
+        for id in processes.resolve(conf.n, &mut 0, SimulationKernel::get_entry_seed(conf, entry)) {
+            let next = generator.next_change(time, true, &mut *self.rng.borrow_mut());
+            if let Some(next) = next {
+                self.add_event(Event::new_churn(next, ChurnKind::Session { entry, id, up: false, leave: generator.leave }));
+            }
+        }
+        self.sessions.insert(entry, generator);
+        Ok(())
+    }
+    /// changes the status of process id, unless it already has that status, and schedules the end of its new session
+    /// a process that has not joined is left out of the sessions, and then returns false
+    fn handle_session(&mut self, ts: Time, entry: usize, id: ProcessId, up: bool, leave: bool, conf: &Conf) -> bool {
+        if !self.has_process_joined(id) {
+            return false;
+        }
+        if up && self.has_process_joined(id) && !self.is_process_up(id) {
+            self.recover_process(ts, id, conf);
+        } else if !up && self.is_process_up(id) {
+            if leave {
+                self.leave_process(ts, id);
+            } else {
+                self.fail_process(ts, id);
+            }
+        }
+        let next = match self.sessions.get(&entry) {
+            Some(generator) => generator.next_change(ts, up, &mut *self.rng.borrow_mut()),
+            None => None
+        };
+        if let Some(next) = next {
+            self.add_event(Event::new_churn(next, ChurnKind::Session { entry, id, up: !up, leave }));
+        }
+        true
+    }
+    /// trace is a mapping with the fields of a TraceConf
+    fn parse_trace(trace: &yaml::Yaml) -> Result<TraceConf, String> {
+        if trace.as_hash().is_none() {
+            return Err("trace expects a mapping with the file and the format of the trace as 3rd parameter, for e.g. {file: traces/nodes.tab, format: fta, time_unit: s}".to_string());
+        }
+        let mut s = String::new();
+        YamlEmitter::new(&mut s).dump(trace).map_err(|e| format!("{:?}", e))?;
+        serde_yaml::from_str(&s).map_err(|e| format!("invalid trace: {}", e))
+    }
+    /// schedules the failures (or leaves) and recoveries of the processes in the trace, which starts at time
+    fn handle_churn_trace(&mut self, time: Time, trace: &TraceConf, conf: &Conf) -> Result<(), Error> {
+        for transition in trace.get_transitions(time, conf.time_unit, conf.n)? {
+            match (transition.up, trace.leave) {
+                (true, _) => self.add_recover_id_event(transition.ts, transition.id),
+                (false, true) => self.add_leave_id_event(transition.ts, transition.id),
+                (false, false) => self.add_fail_id_event(transition.ts, transition.id)
+            }
+        }
+        Ok(())
+    }
+    /// assignment is a mapping with the name of the profile and the processes that get it
+    fn handle_churn_profile(&mut self, time: Time, assignment: &yaml::Yaml, entry: usize, conf: &Conf) -> Result<(), String> {
+        let usage = "profile expects a mapping with the name of the profile and the processes as 3rd parameter, for e.g. {name: slow, processes: {random: 0.1}}";
+        if assignment.as_hash().is_none() {
+            return Err(usage.to_string());
+        }
+        let mut s = String::new();
+        YamlEmitter::new(&mut s).dump(assignment).map_err(|e| format!("{:?}", e))?;
+        let assignment: ProfileAssignment = serde_yaml::from_str(&s).map_err(|e| format!("{}: {}", usage, e))?;
+        if !self.async_profiles.exists(&assignment.name) {
+            return Err(format!("async profile {} does not exist, it must be default or be in async_profiles in the configuration", assignment.name));
+        }
+        assignment.processes.check(conf.n)?;
+        let ids = assignment.processes.resolve(conf.n, &mut 0, SimulationKernel::get_entry_seed(conf, entry));
+
+        self.add_event(Event::new_churn(time, ChurnKind::Profile { name: assignment.name, ids }));
+        Ok(())
+    }
+    /// declares the override and schedules its start and end
+    fn schedule_override(&mut self, name: String, network_override: NetworkOverride) {
+        self.add_event(Event::new_churn(network_override.from, ChurnKind::Override { name: name.clone() }));
+        if let Some(until) = network_override.until {
+            self.add_event(Event::new_churn(until, ChurnKind::OverrideEnd { name: name.clone() }));
+        }
+        self.overrides.borrow_mut().declare(name, network_override);
+    }
+    fn config_overrides(&mut self, conf: &Conf) {
+        for (index, network_override) in conf.network_overrides.iter().enumerate() {
+            let name = network_override.name.clone().unwrap_or_else(|| format!("override-{}", index));
+            self.schedule_override(name, network_override.clone());
         }
     }
     fn no_churn_specified(&mut self, conf: &Conf) {
@@ -836,53 +1673,119 @@ impl SimulationKernel {
             }
         }
     }
-    fn config_churn(&mut self, conf: &Conf) -> bool {
-        let mut exists_end = false;
+    fn config_churn(&mut self, conf: &Conf) -> Result<bool, Error> {
+        if let Some(churn) = &conf.churn {
+            return self.config_churn_from_str(churn, "in-memory churn", conf);
+        }
         match &conf.churn_file {
             None => {
                 self.no_churn_specified(conf);
+                Ok(false)
             },
             Some(filename) => {
-                let s = fs::read_to_string(&filename).expect(&format!("Unable to open and read from file {}", filename));
+                let s = fs::read_to_string(&filename).map_err(|source| Error::Io { file: filename.clone(), source })?;
 
-                let contents = YamlLoader::load_from_str(&s).expect(&format!("File {} does not have yaml format", filename));
-                if contents.len() == 0 {
-                    self.no_churn_specified(conf);
-                    return exists_end;
-                }
-                let content = &contents[0];
-
-                match content["churn"].as_vec() {
-                    None => {},
-                    Some(v) => {
-                        for item in v {
-                            let i = item.as_vec().expect("expected a tuple containing time, action (join/leave/recover), and number of processes");
-                            let time = i[0].as_i64().expect("time must be an integer") as Time;
-                            let action = i[1].as_str().expect("action (join/leave/recover/leave-id/recover-id) must be a string");
-
-                            match action {
-                                "join" | "leave" | "recover" | "fail" => {
-                                    self.handle_churn_num_proc(time, action, &i[2], conf.n);
-                                },
-                                "leave-id" | "recover-id" | "fail-id" => {
-                                    self.handle_churn_id(time, action, &i[2]);
-                                },
-                                "end" => {
-                                    self.add_end_event(time);
-                                    exists_end = true;
-                                },
-                                _ => {
-                                    eprintln!("error: in the churn configuration file the 2nd parameter must be one of the following join, leave, recover, leave-id, recover-id, end");
-                                    ::std::process::exit(-1);
-                                },
-                            }
-                        }
+                self.config_churn_from_str(&s, filename, conf)
+            }
+        }
+    }
+    fn config_churn_from_str(&mut self, s: &str, source: &str, conf: &Conf) -> Result<bool, Error> {
+        let mut exists_end = false;
+
+        let contents = YamlLoader::load_from_str(s).map_err(|e| Error::config(source, Some(e.marker().line()), format!("not in yaml format: {}", e)))?;
+        if contents.len() == 0 {
+            self.no_churn_specified(conf);
+            return Ok(exists_end);
+        }
+        let content = &contents[0];
+
+        match content["churn"].as_vec() {
+            None => {},
+            Some(v) => {
+                let lines = churn_entries_lines(s);
+                let stops = conf.save_and_stop || v.iter().any(|item| item[1].as_str() == Some("end"));
+                // the id actions may also refer to the processes spawned by this churn, which get the ids that follow
+                let spawned: i64 = v.iter()
+                    .filter(|item| item[1].as_str() == Some("spawn"))
+                    .filter_map(|item| item[2].as_i64())
+                    .filter(|&num_proc| num_proc > 0)
+                    .sum();
+                let max_id = conf.n.max(self.processes.borrow().len() as ProcessId) + spawned as ProcessId;
+                for (index, item) in v.iter().enumerate() {
+                    let line = lines.get(index).cloned();
+                    let error = |msg: String| Error::config(source, line, msg);
+
+                    let i = item.as_vec().ok_or_else(|| error("expected a tuple containing time, action (join/leave/recover), and number of processes".to_string()))?;
+                    let time = match i.first() {
+                        Some(yaml::Yaml::Integer(t)) => *t as Time,
+                        Some(yaml::Yaml::String(t)) => conf.time_unit.parse_duration(t).map_err(error)?,
+                        _ => return Err(error("time must be an integer or a duration with unit, for e.g. 90s".to_string()))
+                    };
+                    let action = i.get(1).and_then(|a| a.as_str()).ok_or_else(|| error("action (join/leave/recover/join-id/leave-id/recover-id) must be a string".to_string()))?;
+                    let param = i.get(2).unwrap_or(&yaml::Yaml::BadValue);
+
+                    match action {
+                        "join" | "leave" | "recover" | "fail" => {
+                            self.handle_churn_num_proc(time, action, param, conf.n).map_err(error)?;
+                        },
+                        "join-id" | "leave-id" | "remove-id" | "recover-id" | "fail-id" => {
+                            self.handle_churn_id(time, action, param, max_id).map_err(error)?;
+                        },
+                        "spawn" => {
+                            self.handle_churn_spawn(time, param, conf).map_err(error)?;
+                        },
+                        "fail-domain" | "leave-domain" | "recover-domain" => {
+                            self.handle_churn_domain(time, action, param).map_err(error)?;
+                        },
+                        "partition" => {
+                            self.handle_churn_partition(time, param, index, conf).map_err(error)?;
+                        },
+                        "heal" => {
+                            self.add_event(Event::new_churn(time, ChurnKind::Heal));
+                        },
+                        "override" => {
+                            self.handle_churn_override(time, param, index).map_err(error)?;
+                        },
+                        "profile" => {
+                            self.handle_churn_profile(time, param, index, conf).map_err(error)?;
+                        },
+                        "sessions" => {
+                            self.handle_churn_sessions(time, param, index, stops, conf).map_err(error)?;
+                        },
+                        "trace" => {
+                            let trace = SimulationKernel::parse_trace(param).map_err(error)?;
+                            self.handle_churn_trace(time, &trace, conf)?;
+                        },
+                        "override-end" => {
+                            let name = param.as_str().ok_or_else(|| error("override-end expects the name of the override as 3rd parameter".to_string()))?;
+                            self.add_event(Event::new_churn(time, ChurnKind::OverrideEnd { name: name.to_string() }));
+                        },
+                        "end" => {
+                            self.add_end_event(time);
+                            exists_end = true;
+                        },
+                        _ => {
+                            return Err(error(format!("the 2nd parameter must be one of the following join, leave, fail, recover, spawn, join-id, leave-id, remove-id, fail-id, recover-id, fail-domain, leave-domain, recover-domain, partition, heal, override, override-end, profile, sessions, trace, end but found {}", action)));
+                        },
                     }
                 }
+            }
+        }
 
+        Ok(exists_end)
+    }
+    /// checks the clocks of the processes, including those that the churn will spawn,
+    /// so that a spawned process does not find an invalid clock when it is created
+    fn config_spawned_clocks(&self, conf: &Conf) -> Result<(), Error> {
+        if let Some(clock) = &conf.clock {
+            let n = self.processes.borrow().len() as ProcessId;
+            let max_id = n + self.queue.borrow().spawns();
+            clock.check_ids(max_id).map_err(Error::Simulation)?;
+            for id in n..max_id {
+                clock.get_clock(id, conf.seed).map_err(Error::Simulation)?;
             }
         }
-        exists_end
+        Ok(())
     }
     fn config_save(&mut self, conf: &Conf) -> bool {
         if let Some(ts) = conf.save {
@@ -890,16 +1793,58 @@ impl SimulationKernel {
         }
         conf.save_and_stop
     }
-    pub fn config(&mut self, conf: &Conf) -> bool {
-        let exists_end = self.config_churn(&conf);
+    fn config_profiles(&mut self, conf: &Conf) -> Result<(), Error> {
+        if conf.async_profiles.is_empty() {
+            return Ok(());
+        }
+        let op_duration = SimulationKernel::get_op_duration(conf)?;
+        self.async_profiles = AsyncProfiles::new(conf, op_duration).map_err(Error::Simulation)?;
+        if conf.load.is_none() {
+            for (name, ids) in self.async_profiles.initial_assignments(conf.n, conf.seed).map_err(Error::Simulation)? {
+                for id in ids {
+                    self.set_async_profile(id, &name);
+                }
+            }
+        }
+        Ok(())
+    }
+    /// gives the asynchrony and op_duration of the profile to process id
+    fn set_async_profile(&self, id: ProcessId, name: &str) {
+        if let (Some(process), Some((asynchrony, op_duration))) = (self.get_process(id), self.async_profiles.get(name)) {
+            let mut p = process.borrow_mut();
+            p.asynchrony = Rc::new(RefCell::new(asynchrony));
+            p.op_duration = op_duration;
+            p.profile = if name == profiles::DEFAULT_PROFILE { None } else { Some(name.to_string()) };
+        }
+    }
+    fn set_failure_detector(&self, id: ProcessId, conf: FailureDetectorConf) {
+        if let Some(process) = self.get_process(id) {
+            *process.borrow().detector.borrow_mut() = FailureDetector::new(conf);
+        }
+    }
+    fn set_clock(&self, id: ProcessId, clock: Clock) {
+        if let Some(process) = self.get_process(id) {
+            process.borrow_mut().clock = clock;
+        }
+    }
+    pub fn config(&mut self, conf: &Conf) -> Result<bool, Error> {
+        self.config_profiles(conf)?;
+        if let Some(domains) = &conf.domains {
+            self.domains = FailureDomains::new(domains)?;
+        }
+        self.config_overrides(conf);
+        let exists_end = self.config_churn(&conf)?;
+        self.config_spawned_clocks(conf)?;
         let exists_save_and_stop = self.config_save(conf);
-        exists_end || exists_save_and_stop
+        Ok(exists_end || exists_save_and_stop)
     }
     #[cfg(feature = "test_churn")]
     fn log_churn(&self, ts: Time, kind: ChurnKind, ids: Vec<ProcessId>) {
         let churn = format!("{} {} {} {} {}", ts, kind.to_string(), kind.to_int(), ids.len(), utils::vec_to_str_no_whitespace(ids));
 
-        utils::save_to_file(CHURN_OUTPUT_FILE, churn, true);
+        if let Err(e) = utils::save_to_file(CHURN_OUTPUT_FILE, churn, true) {
+            eprintln!("{}", e);
+        }
     }
     fn clean_files(&self) {
         #[cfg(feature = "test_churn")]
@@ -916,160 +1861,346 @@ impl SimulationKernel {
 
     }
     fn handle_churn_event(&mut self, event: Event, conf: &Conf) {
+        let ts = event.ts;
         if let EventKind::Churn(c) = event.kind {
-            match c {
-                ChurnKind::Join { num_proc } => {
-                    let _ids = self.join_processes(event.ts, conf, num_proc);
-                    #[cfg(feature = "test_churn")]
-                    self.log_churn(event.ts, c, _ids);
+            let ids = match &c {
+                &ChurnKind::Join { num_proc } => {
+                    self.join_processes(event.ts, conf, num_proc)
                 },
-                ChurnKind::Leave { num_proc } => {
-                    let _ids = self.leave_processes(event.ts, num_proc);
-                    #[cfg(feature = "test_churn")]
-                    self.log_churn(event.ts, c, _ids);
+                &ChurnKind::Leave { num_proc } => {
+                    self.leave_processes(event.ts, num_proc)
                 },
-                ChurnKind::Fail { num_proc } => {
-                    let _ids = self.fail_processes(event.ts, num_proc);
-                    #[cfg(feature = "test_churn")]
-                    self.log_churn(event.ts, c, _ids);
+                &ChurnKind::Fail { num_proc } => {
+                    self.fail_processes(event.ts, num_proc)
                 },
-                ChurnKind::Recover { num_proc } => {
-                    let _ids = self.recover_processes(event.ts, conf, num_proc);
-                    #[cfg(feature = "test_churn")]
-                    self.log_churn(event.ts, c, _ids);
+                &ChurnKind::Recover { num_proc } => {
+                    self.recover_processes(event.ts, conf, num_proc)
                 },
-                ChurnKind::LeaveId { id } => {
-                    self.leave_process(event.ts, id);
-
-                    #[cfg(feature = "test_churn")] {
-                        let ids = vec![id];
-                        self.log_churn(event.ts, c, ids);
+                &ChurnKind::JoinId { id } => {
+                    if !self.id_in_use(id) || self.is_process_removed(id) || self.has_process_joined(id) {
+                        Vec::new()
+                    } else {
+                        self.join_process(event.ts, id, conf);
+                        vec![id]
                     }
                 },
-                ChurnKind::FailId { id } => {
+                &ChurnKind::LeaveId { id } => {
+                    self.leave_process(event.ts, id);
+                    vec![id]
+                },
+                &ChurnKind::RemoveId { id } => {
+                    self.remove_process(event.ts, id);
+                    vec![id]
+                },
+                &ChurnKind::Spawn { num_proc } => {
+                    (0..num_proc).filter_map(|_| self.spawn_process(ts, conf)).collect()
+                },
+                &ChurnKind::FailId { id } => {
                     self.fail_process(event.ts, id);
-                    #[cfg(feature = "test_churn")] {
-                        let ids = vec![id];
-                        self.log_churn(event.ts, c, ids);
-                    }
+                    vec![id]
                 },
-                ChurnKind::RecoverId { id } => {
+                &ChurnKind::RecoverId { id } => {
                     self.recover_process(event.ts, id, conf);
-                    #[cfg(feature = "test_churn")] {
-                        let ids = vec![id];
-                        self.log_churn(event.ts, c, ids);
+                    vec![id]
+                },
+                ChurnKind::Partition { groups } => {
+                    self.partition.borrow_mut().split(groups);
+                    groups.iter().flatten().cloned().collect()
+                },
+                ChurnKind::Heal => {
+                    self.partition.borrow_mut().heal();
+                    Vec::new()
+                },
+                ChurnKind::Override { name } => {
+                    let mut overrides = self.overrides.borrow_mut();
+                    overrides.activate(name);
+                    overrides.get(name).map(|o| o.get_ids()).unwrap_or_default()
+                },
+                ChurnKind::OverrideEnd { name } => {
+                    let mut overrides = self.overrides.borrow_mut();
+                    overrides.end(name);
+                    overrides.get(name).map(|o| o.get_ids()).unwrap_or_default()
+                },
+                ChurnKind::Profile { name, ids } => {
+                    for &id in ids {
+                        self.set_async_profile(id, name);
+                    }
+                    ids.clone()
+                },
+                &ChurnKind::Session { entry, id, up, leave } => {
+                    if self.handle_session(event.ts, entry, id, up, leave, conf) {
+                        vec![id]
+                    } else {
+                        Vec::new()
+                    }
+                },
+                ChurnKind::FailDomain { domains, num_domains } => {
+                    let ids = self.get_domains_processes(domains, *num_domains, |kernel, id| kernel.is_process_up(id));
+                    for &id in &ids {
+                        self.fail_process(event.ts, id);
                     }
+                    ids
+                },
+                ChurnKind::LeaveDomain { domains, num_domains } => {
+                    let ids = self.get_domains_processes(domains, *num_domains, |kernel, id| kernel.is_process_up(id));
+                    for &id in &ids {
+                        self.leave_process(event.ts, id);
+                    }
+                    ids
+                },
+                ChurnKind::RecoverDomain { domains, num_domains } => {
+                    let ids = self.get_domains_processes(domains, *num_domains, |kernel, id| kernel.has_process_joined(id) && !kernel.is_process_up(id));
+                    for &id in &ids {
+                        self.recover_process(event.ts, id, conf);
+                    }
+                    ids
                 },
                 ChurnKind::End => {
                     // not supposed to reach this
+                    return;
                 }
             };
+
+            observer::notify(&self.observers, |o| o.on_churn(ts, &c.to_string(), &ids));
+
+            #[cfg(feature = "test_churn")]
+            self.log_churn(ts, c, ids);
         }
     }
     #[cfg(feature = "checkpointing")]
-    fn handle_save_event<P: AsRef<Path> + Display>(&self, ts: Time, save_filename: P) {
+    fn handle_save_event<P: AsRef<Path> + Display>(&self, ts: Time, save_filename: P) -> Result<(), Error> {
         println!("Time {} saving snapshot in file {}", ts, save_filename);
-        utils::save_object_in_binary_file(&self, save_filename);
+        utils::save_object_in_binary_file(&self, &save_filename)?;
+        observer::notify(&self.observers, |o| o.on_save(ts, &save_filename.to_string()));
+        Ok(())
     }
     #[cfg(not(feature = "checkpointing"))]
-    fn handle_save_event<P: AsRef<Path> + Display>(&self, _ts: Time, _save_filename: P) {
-        println!("Unable to save state, due to the checkpointing feature being disabled. ");
-    }
-    pub fn run(&mut self, conf: &Conf) {
-        let ctrlc_received = Arc::new(AtomicBool::new(false));
-        #[cfg(all(feature = "checkpointing_on_ctrlc", not(test)))]
-        {
-            let ctrlc_r = ctrlc_received.clone();
-
-            ctrlc::set_handler(move || {
-                if !ctrlc_r.load(atomic::Ordering::SeqCst) {
-                    ctrlc_r.store(true, atomic::Ordering::SeqCst);
+    fn handle_save_event<P: AsRef<Path> + Display>(&self, _ts: Time, _save_filename: P) -> Result<(), Error> {
+        Err(Error::Simulation("unable to save state, due to the checkpointing feature being disabled".to_string()))
+    }
+    #[cfg(all(feature = "checkpointing_on_ctrlc", not(test)))]
+    fn set_ctrlc_handler() -> Result<(), Error> {
+        let mut result = Ok(());
+        CTRLC_HANDLER.call_once(|| {
+            result = ctrlc::set_handler(|| {
+                if !CTRLC_RECEIVED.load(atomic::Ordering::SeqCst) {
+                    CTRLC_RECEIVED.store(true, atomic::Ordering::SeqCst);
                     println!("\nReceived ctrl-c. A snapshot will be saved. \nIf you want to quit immediately without the snapshot click ctrl-c again. \n");
                 } else {
                     println!("\nReceived 2nd ctrl-c. Exiting.\n");
                     ::std::process::exit(-1);
                 }
-            }).expect("Error setting Ctrl-C handler");
+            }).map_err(|e| Error::Simulation(format!("unable to set the Ctrl-C handler: {}", e)));
+        });
+        result
+    }
+    #[cfg(not(all(feature = "checkpointing_on_ctrlc", not(test))))]
+    fn set_ctrlc_handler() -> Result<(), Error> {
+        Ok(())
+    }
+    pub fn get_state(&self) -> RunState {
+        self.state
+    }
+    pub fn get_time(&self) -> Time {
+        *self.current_ts.borrow()
+    }
+    /// number of events taken from the event queue since the kernel was created or loaded
+    pub fn get_events_processed(&self) -> u64 {
+        self.events_processed
+    }
+    /// executes the next event in the event queue
+    /// once the simulation is finished it does nothing and keeps returning the same state
+    pub fn step(&mut self, conf: &Conf) -> Result<RunState, Error> {
+        if self.state.is_finished() {
+            return Ok(self.state);
         }
 
-        self.clean_files();
+        if self.events_processed == 0 {
+            SimulationKernel::set_ctrlc_handler()?;
+            self.clean_files();
+        }
 
-        //counts total events processed
-        let mut events_processed: u32 = 0;
-        //main simulation loop, run until event queue is empty
-        loop {
-            /*// periodically print the simulation progress
-            if events_processed % 1000000 == 0 {
-                println!("Events processed: {} Events remaining: {} ", events_processed, self.queue.borrow().len());
-            }
-            */
-            //println!("\nEvents in queue {:?}\n", self.queue);
-
-            if cfg!(all(feature = "checkpointing_on_ctrlc", not(test))) {
-                let save = ctrlc_received.load(atomic::Ordering::SeqCst);
-                if save {
-                    let save_filename = "saved_on_exit.bin";
-                    println!("Saving state in file {}", &save_filename);
-                    self.handle_save_event(*self.current_ts.borrow(), &save_filename);
-                    ::std::process::exit(-1);
-                }
+        if cfg!(all(feature = "checkpointing_on_ctrlc", not(test))) && CTRLC_RECEIVED.swap(false, atomic::Ordering::SeqCst) {
+            let save_filename = "saved_on_exit.bin";
+            println!("Saving state in file {}", &save_filename);
+            self.handle_save_event(*self.current_ts.borrow(), &save_filename)?;
+            self.state = RunState::Interrupted;
+            return Ok(self.state);
+        }
+
+        let event = match self.next_event() {
+            Some(event) => event,
+            None => {
+                //simulation finished
+                self.state = RunState::Empty;
+                return Ok(self.state);
             }
+        };
+        *self.current_ts.borrow_mut() = event.ts;
 
-            let event = self.next_event();
-            match event {
-                Some(event) => {
-                    *self.current_ts.borrow_mut() = event.ts;
+        // periodically print the simulation progress
+        if self.events_processed % 1000000 == 0 {
+            println!("Time: {} Events processed: {} Events remaining: {}", event.ts, self.events_processed, self.queue.borrow().len());
+        }
+        self.events_processed += 1;
+
+        if let EventKind::Churn(_) | EventKind::Save = event.kind {
+            observer::notify(&self.observers, |o| o.on_event_executed(event.ts, event.target, &event.kind.to_string()));
+        }
 
-                    // periodically print the simulation progress
-                    if events_processed % 1000000 == 0 {
-                        println!("Time: {} Events processed: {} Events remaining: {}", event.ts, events_processed, self.queue.borrow().len());
+        match event.kind {
+            EventKind::Churn(ChurnKind::End) => {
+                println!("Reached end event at time {}", event.ts);
+                self.state = RunState::End;
+            },
+            EventKind::Churn(_) => {
+                self.handle_churn_event(event, conf);
+            },
+            EventKind::Save => {
+                self.handle_save_event(event.ts, &conf.save_filename)?;
+                if conf.save_and_stop {
+                    self.state = RunState::SaveAndStop;
+                }
+            },
+            _ => {
+                //grab a ref to the targeted process
+                let p = self.get_process(event.target).unwrap();
+                let process = p.clone();
+
+                //skip events for failed processes, where the messages are dropped
+                if !self.is_process_up(p.borrow().id) {
+                    if let EventKind::Message = event.kind {
+                        observer::notify(&self.observers, |o| o.on_message_dropped(event.ts, event.from, event.target, &*event.op));
+                    }
+                    return Ok(self.state);
+                }
+                if let EventKind::Local(generation, _, count, timer) = event.kind {
+                    // cancelled timers are skipped only when due
+                    if p.borrow().get_generation() != generation || !p.borrow().is_pending(timer) {
+                        return Ok(self.state);
+                    }
+                    if count == 0 && !p.borrow().simulation_stops {
+                        return Err(Error::Simulation(format!("process {} scheduled an infinite periodic local call. \nIt is mandatory to exist a stop/end event for the simulation to stop.\nThis stop/event can be 'end' in churn, or save_and_stop in config if there is a save", event.target)));
                     }
+                }
 
-                    match event.kind {
-                        EventKind::Churn(ChurnKind::End) => {
-                            println!("Reached end event at time {}", event.ts);
-                            break;
-                        },
-                        EventKind::Churn(_) => {
-                            self.handle_churn_event(event, conf);
-                        },
-                        EventKind::Save => {
-                            self.handle_save_event(event.ts, &conf.save_filename);
-                            if conf.save_and_stop {
-                                break;
-                            }
-                        },
-                        _ => {
-                            //grab a ref to the targeted process
-                            let p = self.get_process(event.target).unwrap();
-                            let process = p.clone();
+                observer::notify(&self.observers, |o| o.on_event_executed(event.ts, event.target, &event.kind.to_string()));
+
+                let app = self.get_application(event.target).unwrap();
+
+                p.borrow().receive(event, &conf, app, process);
+            }
+        }
+
+        Ok(self.state)
+    }
+    /// executes all the events with timestamp up to ts (inclusive)
+    /// afterwards the simulation time is ts, unless the simulation finished before reaching it
+    pub fn run_until(&mut self, ts: Time, conf: &Conf) -> Result<RunState, Error> {
+        while !self.state.is_finished() {
+            let next_ts = self.queue.borrow().next_ts();
+            match next_ts {
+                Some(next_ts) if next_ts <= ts => {
+                    self.step(conf)?;
+                },
+                Some(_) => {
+                    if *self.current_ts.borrow() < ts {
+                        *self.current_ts.borrow_mut() = ts;
+                    }
+                    break;
+                },
+                None => {
+                    // let step find the empty queue
+                    self.step(conf)?;
+                }
+            }
+        }
+        Ok(self.state)
+    }
+    /// executes at most num_events events
+    pub fn run_for(&mut self, num_events: u64, conf: &Conf) -> Result<RunState, Error> {
+        for _ in 0..num_events {
+            if self.step(conf)?.is_finished() {
+                break;
+            }
+        }
+        Ok(self.state)
+    }
+    /// executes events until the simulation finishes
+    pub fn run(&mut self, conf: &Conf) -> Result<RunState, Error> {
+        //main simulation loop, run until event queue is empty or the simulation stops
+        while !self.step(conf)?.is_finished() {}
 
+        println!("Time: {}. Total events processed: {}. Events still in event queue: {}", *self.current_ts.borrow(), self.events_processed, self.queue.borrow().len());
 
-                            //skip events for failed processes
-                            if !self.is_process_up(p.borrow().id) {
-                                continue;
-                            }
-                            if let EventKind::Local(generation, _, _) = event.kind {
-                                if p.borrow().get_generation() != generation {
-                                    continue;
-                                }
-                            }
+        Ok(self.state)
+    }
+}
 
-                            //println!("Event being processed: {:?}", event);
+/// returns the line (starting at 1) of each entry of the "churn" sequence in a churn file,
+/// so that errors in an entry can point to where it is written
+fn churn_entries_lines(s: &str) -> Vec<usize> {
+    enum Node {
+        Sequence { churn: bool },
+        Mapping { expect_key: bool, key: Option<String> }
+    }
 
-                            let app = self.get_application(event.target).unwrap();
+    struct ChurnLines {
+        stack: Vec<Node>,
+        documents: u32,
+        lines: Vec<usize>
+    }
 
-                            p.borrow().receive(event, &conf, app, process);
-                        }
+    impl ChurnLines {
+        /// returns true if the node starting is the value of the top level key "churn"
+        fn node_start(&mut self, line: usize, scalar: Option<String>) -> bool {
+            let depth = self.stack.len();
+            match self.stack.last_mut() {
+                Some(Node::Sequence { churn: true }) => {
+                    self.lines.push(line);
+                    false
+                },
+                Some(Node::Mapping { expect_key, key }) => {
+                    if *expect_key {
+                        *expect_key = false;
+                        *key = scalar;
+                        false
+                    } else {
+                        *expect_key = true;
+                        depth == 1 && key.as_ref().map(|k| k == "churn").unwrap_or(false)
                     }
                 },
-                None => break, //simulation finished
+                _ => false
             }
-            events_processed += 1;
         }
+    }
 
-        println!("Time: {}. Total events processed: {}. Events still in event queue: {}", *self.current_ts.borrow(), events_processed, self.queue.borrow().len());
+    impl MarkedEventReceiver for ChurnLines {
+        fn on_event(&mut self, event: YamlEvent, mark: Marker) {
+            if self.documents > 1 {
+                return;
+            }
+            match event {
+                YamlEvent::DocumentStart => { self.documents += 1; },
+                YamlEvent::Scalar(value, _, _, _) => { self.node_start(mark.line(), Some(value)); },
+                YamlEvent::Alias(_) => { self.node_start(mark.line(), None); },
+                YamlEvent::SequenceStart(_) => {
+                    let churn = self.node_start(mark.line(), None);
+                    self.stack.push(Node::Sequence { churn });
+                },
+                YamlEvent::MappingStart(_) => {
+                    self.node_start(mark.line(), None);
+                    self.stack.push(Node::Mapping { expect_key: true, key: None });
+                },
+                YamlEvent::SequenceEnd | YamlEvent::MappingEnd => { self.stack.pop(); },
+                _ => {}
+            }
+        }
     }
+
+    let mut receiver = ChurnLines { stack: Vec::new(), documents: 0, lines: Vec::new() };
+    let _ = Parser::new(s.chars()).load(&mut receiver, false);
+    receiver.lines
 }
 
 #[allow(unused_imports)]
@@ -1096,29 +2227,96 @@ mod test {
     use crate::simulation::Event;
     use crate::simulation::Time;
     use crate::simulation::Process;
+    use crate::simulation::KernelHandles;
     use crate::simulation::ProcessId;
     use crate::simulation::EventKind;
     use crate::simulation::ChurnKind;
     use crate::simulation::EventQueue;
+    use crate::simulation::TieBreaking;
     use crate::simulation::ApplicationBase;
     use crate::simulation::SimulationKernel;
     use crate::simulation::asynchrony::{Asynchrony, NoAsynchrony, UniformAsynchrony, NormalAsynchrony, WeibullAsynchrony};
     use crate::simulation::network::{self, ConstantNetwork, Network, NoJitter};
     use crate::simulation::utils;
+    use crate::Error;
 
     use crate::echo_lib::Application;
     use crate::echo_lib::AppConf;
     use crate::echo_lib::stats;
 
+    /// asserts that result is a configuration error at line, and returns the file of the error
+    pub(crate) fn assert_config_error<T>(result: Result<T, Error>, line: Option<usize>) -> String {
+        match result {
+            Err(Error::Config { file, line: error_line, .. }) => {
+                assert_eq!(error_line, line);
+                file
+            },
+            Err(e) => panic!("expected a configuration error, got {:?}", e),
+            Ok(_) => panic!("expected a configuration error, got Ok")
+        }
+    }
+    pub(crate) fn echo_apps(app_conf: Rc<AppConf>) -> Vec<Rc<RefCell<Box<dyn ApplicationBase>>>> {
+        (0..app_conf.n).map(|i| Rc::new(RefCell::new(Box::new(Application::new(i, 0, 0, 0, app_conf.clone())) as Box<dyn ApplicationBase>))).collect()
+    }
+
+    /// observer that records the local events executed by the processes and the churn
+    #[derive(Default)]
+    pub(crate) struct Recorder {
+        pub(crate) local: Vec<(Time, ProcessId)>,
+        pub(crate) churn: Vec<(Time, String, Vec<ProcessId>)>
+    }
+
+    impl Recorder {
+        /// churn in which processes fail or recover
+        pub(crate) fn failures(&self) -> Vec<(Time, String, Vec<ProcessId>)> {
+            self.churn.iter().filter(|c| c.1 == "fail" || c.1 == "recover").cloned().collect()
+        }
+    }
+
+    impl simulation::observer::SimulationObserver for Recorder {
+        fn on_event_executed(&mut self, ts: Time, target: ProcessId, kind: &str) {
+            if kind == "local" {
+                self.local.push((ts, target));
+            }
+        }
+        fn on_churn(&mut self, ts: Time, kind: &str, ids: &[ProcessId]) {
+            self.churn.push((ts, kind.to_string(), ids.to_vec()));
+        }
+    }
+
+    /// adds a new Recorder to the observers of kernel
+    pub(crate) fn record(kernel: &mut SimulationKernel) -> Rc<RefCell<Recorder>> {
+        let recorder = Rc::new(RefCell::new(Recorder::default()));
+        kernel.add_observer(recorder.clone());
+        recorder
+    }
+
+    /// path of the snapshot of a test in the temporary directory
+    pub(crate) fn snapshot_path(name: &str) -> String {
+        std::env::temp_dir().join(format!("corten-{}-{}.bin", std::process::id(), name)).to_string_lossy().into_owned()
+    }
+
+    /// loads the snapshot with builder, deletes it, and runs the loaded kernel with a Recorder
+    #[cfg(feature = "checkpointing")]
+    pub(crate) fn run_loaded(builder: simulation::SimulationBuilder, snapshot: &str) -> (SimulationKernel, Rc<RefCell<Recorder>>) {
+        let builder = builder.load(snapshot);
+        let result = builder.build(&Vec::new());
+        let _ = fs::remove_file(snapshot);
+        let mut kernel = result.unwrap();
+        let recorder = record(&mut kernel);
+        kernel.run(builder.conf()).unwrap();
+        (kernel, recorder)
+    }
+
     #[cfg(feature = "test_network")]
     fn network_main<P: AsRef<Path> + Display>(conf_filename: P) {
-        let app_conf: Rc<AppConf> = Rc::new(utils::yaml_from_file_to_object(&conf_filename));
+        let app_conf: Rc<AppConf> = Rc::new(utils::yaml_from_file_to_object(&conf_filename).unwrap());
 
         let mut apps = Vec::new();
         for i in 0..app_conf.n {
             apps.push(Rc::new(RefCell::new(Box::new(Application::new(i, 0, 0, 0, app_conf.clone())) as Box<dyn ApplicationBase>)));
         }
-        let kernel = SimulationKernel::init(&apps, conf_filename);
+        let kernel = SimulationKernel::init(&apps, conf_filename).unwrap();
     }
 
     #[test]
@@ -1140,13 +2338,13 @@ mod test {
     #[cfg(feature = "test_churn")]
     fn churn_main() {
         let conf_filename = "config/test/conf-churn.yaml";
-        let app_conf: Rc<AppConf> = Rc::new(utils::yaml_from_file_to_object(&conf_filename));
+        let app_conf: Rc<AppConf> = Rc::new(utils::yaml_from_file_to_object(&conf_filename).unwrap());
 
         let mut apps = Vec::new();
         for i in 0..app_conf.n {
             apps.push(Rc::new(RefCell::new(Box::new(Application::new(i, 0, 0, 0, app_conf.clone())) as Box<dyn ApplicationBase>)));
         }
-        let kernel = SimulationKernel::init(&apps, conf_filename);
+        let kernel = SimulationKernel::init(&apps, conf_filename).unwrap();
     }
 
     #[test]
@@ -1158,8 +2356,8 @@ mod test {
     #[cfg(feature = "test_async")]
     fn async_main(asynchrony: Rc<RefCell<Box<Asynchrony>>>) {
         let conf_filename = "config/test/conf-async.yaml";
-        let conf: Conf = utils::yaml_from_file_to_object(&conf_filename);
-        let app_conf: Rc<AppConf> = Rc::new(utils::yaml_from_file_to_object(&conf_filename));
+        let conf: Conf = utils::yaml_from_file_to_object(&conf_filename).unwrap();
+        let app_conf: Rc<AppConf> = Rc::new(utils::yaml_from_file_to_object(&conf_filename).unwrap());
 
         let op_duration = conf.op_duration.unwrap();
 
@@ -1171,9 +2369,9 @@ mod test {
             kernel.add_process(i, app, op_duration, asynchrony.clone(), network.clone());
         }
 
-        kernel.config(&conf);
+        kernel.config(&conf).unwrap();
 
-        kernel.run(&conf);
+        kernel.run(&conf).unwrap();
     }
 
     #[test]
@@ -1208,6 +2406,825 @@ mod test {
         async_main(Rc::new(RefCell::new(Box::new(WeibullAsynchrony::default()))));
     }
 
+    #[test]
+    fn test_churn_error_points_to_line() {
+        let app_conf: Rc<AppConf> = Rc::new(AppConf::new(10, 2, 2, 200));
+        let apps = echo_apps(app_conf);
+
+        let builder = simulation::SimulationBuilder::new(10)
+            .asynchrony(Box::new(NoAsynchrony::new()))
+            .churn("churn:\n  - [0, join, 1.0]\n  - [100, leave-id, 10]\n");
+        assert_config_error(builder.build(&apps), Some(3));
+
+        let builder = simulation::SimulationBuilder::new(10)
+            .asynchrony(Box::new(NoAsynchrony::new()))
+            .churn("churn:\n  - [0, join, 1.0]\n\n  - [100, explode, 1]\n");
+        assert_config_error(builder.build(&apps), Some(4));
+    }
+
+    #[test]
+    fn test_missing_op_duration() {
+        let builder = simulation::SimulationBuilder::new(0)
+            .asynchrony(Box::new(UniformAsynchrony::default()));
+
+        assert!(builder.build(&Vec::new()).is_err());
+    }
+
+    #[test]
+    fn test_churn_durations() {
+        let app_conf: Rc<AppConf> = Rc::new(AppConf::new(2, 1, 1, 100));
+        let apps = echo_apps(app_conf);
+
+        // beyond the 24 days that fit in 32 bits with milliseconds
+        let builder = simulation::SimulationBuilder::new(2)
+            .asynchrony(Box::new(NoAsynchrony::new()))
+            .churn("churn:\n  - [0, join, 1.0]\n  - [1.5s, fail-id, 1]\n  - [30d, end]");
+        let mut kernel = builder.build(&apps).unwrap();
+        kernel.run_until(1499, builder.conf()).unwrap();
+        assert_eq!(kernel.get_processes_up().len(), 2);
+        kernel.run_until(1500, builder.conf()).unwrap();
+        assert_eq!(kernel.get_processes_up().len(), 1);
+        assert_eq!(kernel.run(builder.conf()).unwrap(), simulation::RunState::End);
+        assert_eq!(kernel.get_time(), 30 * 24 * 3600 * 1000);
+
+        let builder = simulation::SimulationBuilder::new(2)
+            .asynchrony(Box::new(NoAsynchrony::new()))
+            .time_unit(simulation::TimeUnit::Us)
+            .churn("churn:\n  - [0, join, 1.0]\n  - [2ms, end]");
+        let mut kernel = builder.build(&apps).unwrap();
+        kernel.run(builder.conf()).unwrap();
+        assert_eq!(kernel.get_time(), 2000);
+
+        let builder = simulation::SimulationBuilder::new(2)
+            .asynchrony(Box::new(NoAsynchrony::new()))
+            .churn("churn:\n  - [0, join, 1.0]\n  - [2 weeks, end]");
+        assert_config_error(builder.build(&apps), Some(3));
+    }
+
+    #[cfg(feature = "checkpointing")]
+    #[derive(Default)]
+    struct CrossGroupCounter {
+        cross_group_sent: Vec<Time>,
+        dropped: u32
+    }
+
+    #[cfg(feature = "checkpointing")]
+    impl simulation::observer::SimulationObserver for CrossGroupCounter {
+        fn on_message_sent(&mut self, ts: Time, from: ProcessId, to: ProcessId, _delivery_ts: Time, _op: &dyn simulation::Operation) {
+            if (from < 5) != (to < 5) {
+                self.cross_group_sent.push(ts);
+            }
+        }
+        fn on_message_dropped(&mut self, _ts: Time, from: ProcessId, to: ProcessId, _op: &dyn simulation::Operation) {
+            assert!((from < 5) != (to < 5));
+            self.dropped += 1;
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "checkpointing")]
+    fn test_partition() {
+        let app_conf: Rc<AppConf> = Rc::new(AppConf::new(10, 3, 10, 100));
+        let apps = echo_apps(app_conf);
+
+        let churn = "churn:\n  - [0, join, 1.0]\n  - [0, partition, [\"0..5\"]]\n  - [500, heal]\n  - [2000, end]";
+        let snapshot = snapshot_path("partition");
+        let builder = simulation::SimulationBuilder::new(10)
+            .asynchrony(Box::new(NoAsynchrony::new()))
+            .churn(churn)
+            .save(300)
+            .save_filename(snapshot.as_str());
+        let mut kernel = builder.build(&apps).unwrap();
+        let counter = Rc::new(RefCell::new(CrossGroupCounter::default()));
+        kernel.add_observer(counter.clone());
+        kernel.run_until(300, builder.conf()).unwrap();
+        assert!(kernel.partition.borrow().is_partitioned());
+        kernel.run(builder.conf()).unwrap();
+        assert!(!kernel.partition.borrow().is_partitioned());
+
+        let c = counter.borrow();
+        assert!(c.dropped > 0);
+        assert!(!c.cross_group_sent.is_empty());
+        assert!(c.cross_group_sent.iter().all(|&ts| ts >= 500));
+
+        // the partition is restored from the snapshot
+        let builder = simulation::SimulationBuilder::new(10)
+            .churn(churn)
+            .load(snapshot.as_str());
+        let mut kernel2 = builder.build(&Vec::new()).unwrap();
+        let _ = fs::remove_file(&snapshot);
+        let counter2 = Rc::new(RefCell::new(CrossGroupCounter::default()));
+        kernel2.add_observer(counter2.clone());
+        assert!(kernel2.partition.borrow().is_partitioned());
+        kernel2.run(builder.conf()).unwrap();
+
+        assert_eq!(counter2.borrow().cross_group_sent, c.cross_group_sent);
+        assert_eq!(stats(kernel.get_applications()), stats(kernel2.get_applications()));
+    }
+
+    thread_local! {
+        static PROBES_RECEIVED: RefCell<Vec<(u32, bool, bool)>> = const { RefCell::new(Vec::new()) };
+    }
+
+    /// message that records whether it was received corrupted
+    #[cfg_attr(feature = "checkpointing", derive(Serialize, Deserialize))]
+    #[derive(Debug)]
+    struct Probe {
+        id: u32,
+        damaged: bool
+    }
+
+    #[cfg_attr(feature = "checkpointing", typetag::serde)]
+    impl simulation::Operation for Probe {
+        fn invoke(&self, _app: Rc<RefCell<Box<dyn ApplicationBase>>>, process: Rc<RefCell<Process>>) {
+            PROBES_RECEIVED.with(|r| r.borrow_mut().push((self.id, self.damaged, process.borrow().is_corrupted())));
+        }
+        fn corrupt(&self, _rng: &mut XorShiftRng) -> Option<Box<dyn simulation::Operation>> {
+            if self.id < 2 {
+                Some(Box::new(Probe { id: self.id, damaged: true }))
+            } else {
+                None
+            }
+        }
+    }
+
+    #[test]
+    fn test_message_faults() {
+        let app_conf: Rc<AppConf> = Rc::new(AppConf::new(10, 2, 3, 100));
+        let churn = "churn:\n  - [0, join, 1.0]\n  - [1000, end]";
+
+        // every echo is received twice, and so every reply is sent twice
+        let duplicate = Box::new(network::FaultyNetwork::new(Box::new(ConstantNetwork::default()), 1.0, 0.0, 0, 0.0));
+        let builder = simulation::SimulationBuilder::new(10)
+            .network(duplicate)
+            .asynchrony(Box::new(NoAsynchrony::new()))
+            .churn(churn);
+        let mut kernel = builder.build(&echo_apps(app_conf.clone())).unwrap();
+        kernel.run(builder.conf()).unwrap();
+        let (sent, received, _) = stats(kernel.get_applications());
+        assert_eq!(received.iter().sum::<i32>(), 2 * sent.iter().sum::<i32>());
+
+        // probes are corrupted by the network, and the first two are replaced by Operation::corrupt
+        let corrupt = Box::new(network::FaultyNetwork::new(Box::new(ConstantNetwork::default()), 0.0, 0.0, 0, 1.0));
+        let silent_conf = Rc::new(AppConf::new(2, 0, 0, 100));
+        let apps = echo_apps(silent_conf);
+        let builder = simulation::SimulationBuilder::new(2)
+            .network(corrupt)
+            .asynchrony(Box::new(NoAsynchrony::new()))
+            .churn(churn);
+        let mut kernel = builder.build(&apps).unwrap();
+        kernel.run_until(0, builder.conf()).unwrap();
+        for id in 0..4 {
+            kernel.processes.borrow()[0].process.borrow().send(Box::new(Probe { id, damaged: false }), 1);
+        }
+        kernel.run(builder.conf()).unwrap();
+        PROBES_RECEIVED.with(|r| assert_eq!(*r.borrow(), vec![(0, true, true), (1, true, true), (2, false, true), (3, false, true)]));
+        assert!(!kernel.processes.borrow()[1].process.borrow().is_corrupted());
+    }
+
+    /// message with the given size in bytes
+    #[cfg_attr(feature = "checkpointing", derive(Serialize, Deserialize))]
+    #[derive(Debug)]
+    struct Payload {
+        size: u64
+    }
+
+    #[cfg_attr(feature = "checkpointing", typetag::serde)]
+    impl simulation::Operation for Payload {
+        fn invoke(&self, _app: Rc<RefCell<Box<dyn ApplicationBase>>>, _process: Rc<RefCell<Process>>) {}
+        fn size(&self) -> u64 {
+            self.size
+        }
+    }
+
+    #[derive(Default)]
+    struct DeliveryTimes(Vec<(ProcessId, Time)>);
+
+    impl simulation::observer::SimulationObserver for DeliveryTimes {
+        fn on_message_sent(&mut self, _ts: Time, _from: ProcessId, to: ProcessId, delivery_ts: Time, _op: &dyn simulation::Operation) {
+            self.0.push((to, delivery_ts));
+        }
+    }
+
+    #[test]
+    fn test_bandwidth() {
+        let silent_conf = Rc::new(AppConf::new(3, 0, 0, 100));
+        let apps = echo_apps(silent_conf);
+        let builder = simulation::SimulationBuilder::new(3)
+            .asynchrony(Box::new(NoAsynchrony::new()))
+            .bandwidth(simulation::BandwidthConf { uplink: 10_000, downlink: 5_000, capacities_file: None })
+            .churn("churn:\n  - [0, join, 1.0]\n  - [10000, end]");
+        let mut kernel = builder.build(&apps).unwrap();
+        let deliveries = Rc::new(RefCell::new(DeliveryTimes::default()));
+        kernel.add_observer(deliveries.clone());
+        kernel.run_until(0, builder.conf()).unwrap();
+
+        {
+            let processes = kernel.processes.borrow();
+            // each message takes 100 to be sent, 100 of latency and 200 to be received
+            processes[0].process.borrow().send(Box::new(Payload { size: 1000 }), 1);
+            processes[0].process.borrow().send(Box::new(Payload { size: 1000 }), 2);
+            // waits for the downlink of 1, which is busy until 400
+            processes[2].process.borrow().send(Box::new(Payload { size: 1000 }), 1);
+            processes[2].process.borrow().send(Box::new(Payload { size: 0 }), 1);
+        }
+        kernel.run(builder.conf()).unwrap();
+
+        assert_eq!(deliveries.borrow().0, vec![(1, 400), (2, 500), (1, 600), (1, 100)]);
+
+        // the message from 0 has 1000 more of latency, so the message from 2 that arrives first is received first
+        let slow = simulation::NetworkOverride { links: vec![(0, 1)], directed: true, extra_latency: 1000, ..Default::default() };
+        let builder = simulation::SimulationBuilder::new(3)
+            .asynchrony(Box::new(NoAsynchrony::new()))
+            .bandwidth(simulation::BandwidthConf { uplink: 10_000, downlink: 5_000, capacities_file: None })
+            .network_override(slow)
+            .churn("churn:\n  - [0, join, 1.0]\n  - [10000, end]");
+        let mut kernel = builder.build(&apps).unwrap();
+        let deliveries = Rc::new(RefCell::new(DeliveryTimes::default()));
+        kernel.add_observer(deliveries.clone());
+        kernel.run_until(0, builder.conf()).unwrap();
+        {
+            let processes = kernel.processes.borrow();
+            processes[0].process.borrow().send(Box::new(Payload { size: 1000 }), 1);
+            processes[2].process.borrow().send(Box::new(Payload { size: 1000 }), 1);
+        }
+        kernel.run(builder.conf()).unwrap();
+
+        assert_eq!(deliveries.borrow().0, vec![(1, 1400), (1, 400)]);
+    }
+
+    #[test]
+    fn test_network_overrides() {
+        let silent_conf = Rc::new(AppConf::new(3, 0, 0, 100));
+        let apps = echo_apps(silent_conf);
+        let slow = simulation::NetworkOverride { name: Some("slow".to_string()), processes: vec![2], latency_factor: 3.0, ..Default::default() };
+        let churn = "churn:\n  - [0, join, 1.0]\n  - [0, override, {links: [[1, 0]], directed: true, loss: 1.0}]\n  - [500, override, {links: [[0, 1]], extra_latency: 50, until: 800}]\n  - [600, override-end, slow]\n  - [2000, end]";
+        let builder = simulation::SimulationBuilder::new(3)
+            .asynchrony(Box::new(NoAsynchrony::new()))
+            .network_override(slow)
+            .churn(churn);
+        let mut kernel = builder.build(&apps).unwrap();
+        let deliveries = Rc::new(RefCell::new(DeliveryTimes::default()));
+        kernel.add_observer(deliveries.clone());
+
+        let send = |kernel: &SimulationKernel, from: ProcessId, to: ProcessId| {
+            kernel.processes.borrow()[from as usize].process.borrow().send(Box::new(Payload { size: 0 }), to);
+        };
+        kernel.run_until(0, builder.conf()).unwrap();
+        send(&kernel, 0, 1);
+        send(&kernel, 0, 2);
+        send(&kernel, 1, 0);
+        kernel.run_until(500, builder.conf()).unwrap();
+        send(&kernel, 0, 1);
+        send(&kernel, 1, 2);
+        kernel.run_until(700, builder.conf()).unwrap();
+        send(&kernel, 1, 0);
+        send(&kernel, 1, 2);
+        kernel.run_until(900, builder.conf()).unwrap();
+        send(&kernel, 0, 1);
+        kernel.run(builder.conf()).unwrap();
+
+        assert_eq!(deliveries.borrow().0, vec![(1, 100), (2, 300), (1, 650), (2, 800), (2, 800), (1, 1000)]);
+
+        let builder = simulation::SimulationBuilder::new(3)
+            .asynchrony(Box::new(NoAsynchrony::new()))
+            .churn("churn:\n  - [0, join, 1.0]\n  - [5, override, 3]");
+        assert_config_error(builder.build(&apps), Some(3));
+    }
+
+    #[test]
+    #[cfg(feature = "checkpointing")]
+    fn test_async_profiles() {
+        let app_conf = Rc::new(AppConf::new(3, 0, 4, 100));
+        let apps = echo_apps(app_conf);
+        // delays each call of the slow processes by op_duration * op_duration_factor = 50
+        let slow = simulation::AsyncProfile::new("slow", Some(Box::new(UniformAsynchrony::new(1.0, 1.0))), 5.0, Some(simulation::ProcessSelection::List(vec![1])));
+        let churn = "churn:\n  - [0, join, 1.0]\n  - [250, profile, {name: slow, processes: 2}]\n  - [250, profile, {name: default, processes: [1]}]\n  - [1000, end]";
+        let snapshot = snapshot_path("profiles");
+        let builder = simulation::SimulationBuilder::new(3)
+            .asynchrony(Box::new(NoAsynchrony::new()))
+            .op_duration(10)
+            .async_profile(slow.clone())
+            .churn(churn)
+            .save(250)
+            .save_filename(snapshot.as_str());
+        let mut kernel = builder.build(&apps).unwrap();
+        let events = record(&mut kernel);
+        kernel.run(builder.conf()).unwrap();
+
+        let of = |id: ProcessId| events.borrow().local.iter().filter(|e| e.1 == id).map(|e| e.0).collect::<Vec<Time>>();
+        assert_eq!(of(0), vec![100, 200, 300, 400]);
+        assert_eq!(of(1), vec![150, 300, 400, 500]);
+        assert_eq!(of(2), vec![100, 200, 300, 450]);
+        assert_eq!(kernel.get_process(1).unwrap().borrow().get_profile(), None);
+        assert_eq!(kernel.get_process(2).unwrap().borrow().get_profile(), Some("slow"));
+
+        // the profiles of the processes are kept in the snapshot
+        let builder = simulation::SimulationBuilder::new(3)
+            .asynchrony(Box::new(NoAsynchrony::new()))
+            .op_duration(10)
+            .async_profile(slow)
+            .churn(churn);
+        let (_, events2) = run_loaded(builder, &snapshot);
+        let after_save: Vec<(Time, ProcessId)> = events.borrow().local.iter().filter(|e| e.0 > 250).cloned().collect();
+        assert_eq!(events2.borrow().local, after_save);
+
+        let builder = simulation::SimulationBuilder::new(3)
+            .asynchrony(Box::new(NoAsynchrony::new()))
+            .churn("churn:\n  - [0, join, 1.0]\n  - [5, profile, {name: fast, processes: 0}]");
+        assert_config_error(builder.build(&apps), Some(3));
+    }
+
+    #[test]
+    #[cfg(feature = "checkpointing")]
+    fn test_clock_drift() {
+        let app_conf = Rc::new(AppConf::new(2, 0, 3, 100));
+        let apps = echo_apps(app_conf);
+        // the clock of process 1 runs 25% faster, so its calls of 100 take 80 of global time
+        let clock = simulation::ClockConf { processes: vec![simulation::clock::ProcessClock { id: 1, offset: 1000, drift: 0.25 }], ..Default::default() };
+        let snapshot = snapshot_path("clock");
+        let builder = simulation::SimulationBuilder::new(2)
+            .asynchrony(Box::new(NoAsynchrony::new()))
+            .clock(clock.clone())
+            .churn("churn:\n  - [0, join, 1.0]\n  - [1000, end]")
+            .save(150)
+            .save_filename(snapshot.as_str());
+        let mut kernel = builder.build(&apps).unwrap();
+        let events = record(&mut kernel);
+        kernel.run(builder.conf()).unwrap();
+
+        assert_eq!(events.borrow().local, vec![(80, 1), (100, 0), (160, 1), (200, 0), (240, 1), (300, 0)]);
+        assert_eq!(kernel.get_time(), 1000);
+        assert_eq!(kernel.get_process(0).unwrap().borrow().get_time(), 1000);
+        assert_eq!(kernel.get_process(1).unwrap().borrow().get_time(), 2250);
+
+        let builder = simulation::SimulationBuilder::new(2)
+            .asynchrony(Box::new(NoAsynchrony::new()))
+            .clock(clock)
+            .churn("churn:\n  - [0, join, 1.0]\n  - [1000, end]");
+        let (_, events2) = run_loaded(builder, &snapshot);
+        assert_eq!(events2.borrow().local, vec![(160, 1), (200, 0), (240, 1), (300, 0)]);
+
+        let builder = simulation::SimulationBuilder::new(2)
+            .asynchrony(Box::new(NoAsynchrony::new()))
+            .clock(simulation::ClockConf { drift: -1.5, ..Default::default() });
+        assert!(builder.build(&apps).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "checkpointing")]
+    fn test_cancel_timers() {
+        // the single cycle of the applications is due after the end of the simulation
+        let app_conf = Rc::new(AppConf::new(2, 0, 1, 100_000));
+        let apps = echo_apps(app_conf);
+        let churn = "churn:\n  - [0, join, 1.0]\n  - [1000, end]";
+        let snapshot = snapshot_path("timers");
+        let builder = simulation::SimulationBuilder::new(2)
+            .asynchrony(Box::new(NoAsynchrony::new()))
+            .churn(churn)
+            .save(250)
+            .save_filename(snapshot.as_str());
+        let mut kernel = builder.build(&apps).unwrap();
+        let events = record(&mut kernel);
+        kernel.run_until(0, builder.conf()).unwrap();
+
+        let p0 = kernel.get_process(0).unwrap();
+        let p1 = kernel.get_process(1).unwrap();
+        let periodic = p0.borrow().periodic(Box::new(Payload { size: 0 }), 100, 0);
+        let call = p0.borrow().call(Box::new(Payload { size: 0 }), 250);
+        let other = p1.borrow().call(Box::new(Payload { size: 0 }), 300);
+        assert_ne!(periodic, call);
+        kernel.run_until(220, builder.conf()).unwrap();
+
+        assert!(p0.borrow().cancel(periodic));
+        assert!(!p0.borrow().cancel(periodic));
+        assert!(p0.borrow().cancel(call));
+        assert!(p1.borrow().is_pending(other));
+        kernel.run(builder.conf()).unwrap();
+
+        assert_eq!(events.borrow().local, vec![(100, 0), (200, 0), (300, 1)]);
+        assert!(!p1.borrow().is_pending(other));
+        assert!(!p1.borrow().cancel(other));
+
+        // the cancelled timers are still skipped after a load
+        let builder = simulation::SimulationBuilder::new(2)
+            .asynchrony(Box::new(NoAsynchrony::new()))
+            .churn(churn);
+        let (_, events2) = run_loaded(builder, &snapshot);
+        assert_eq!(events2.borrow().local, vec![(300, 1)]);
+    }
+
+    thread_local! {
+        static RPC_LOG: RefCell<Vec<(Time, ProcessId, String)>> = const { RefCell::new(Vec::new()) };
+    }
+
+    /// records that it was executed by the process
+    #[cfg_attr(feature = "checkpointing", derive(Serialize, Deserialize))]
+    #[derive(Debug)]
+    struct RpcStep {
+        name: String
+    }
+
+    #[cfg_attr(feature = "checkpointing", typetag::serde)]
+    impl simulation::Operation for RpcStep {
+        fn invoke(&self, _app: Rc<RefCell<Box<dyn ApplicationBase>>>, process: Rc<RefCell<Process>>) {
+            let p = process.borrow();
+            RPC_LOG.with(|r| r.borrow_mut().push((p.get_time(), p.get_id(), self.name.clone())));
+        }
+    }
+
+    /// request to which every process but 2 replies
+    #[cfg_attr(feature = "checkpointing", derive(Serialize, Deserialize))]
+    #[derive(Debug)]
+    struct Ping;
+
+    #[cfg_attr(feature = "checkpointing", typetag::serde)]
+    impl simulation::Operation for Ping {
+        fn invoke(&self, _app: Rc<RefCell<Box<dyn ApplicationBase>>>, process: Rc<RefCell<Process>>) {
+            let p = process.borrow();
+            let request = p.get_request().unwrap();
+            assert_eq!(request.sender, 0);
+            if p.get_id() != 2 {
+                p.reply(request, Box::new(RpcStep { name: format!("pong from {}", p.get_id()) }));
+            }
+        }
+    }
+
+    #[test]
+    fn test_rpc() {
+        let silent_conf = Rc::new(AppConf::new(3, 0, 1, 100_000));
+        let apps = echo_apps(silent_conf);
+        let builder = simulation::SimulationBuilder::new(3)
+            .asynchrony(Box::new(NoAsynchrony::new()))
+            .churn("churn:\n  - [0, join, 1.0]\n  - [1000, end]");
+        let mut kernel = builder.build(&apps).unwrap();
+        kernel.run_until(0, builder.conf()).unwrap();
+
+        let step = |name: &str| Box::new(RpcStep { name: name.to_string() });
+        let p0 = kernel.get_process(0).unwrap();
+        // each message takes 100, so the reply of 1 arrives at 200
+        let answered = p0.borrow().request(Box::new(Ping), 1, step("reply 1"), step("timeout 1"), 500);
+        let unanswered = p0.borrow().request(Box::new(Ping), 2, step("reply 2"), step("timeout 2"), 500);
+        let late = p0.borrow().request(Box::new(Ping), 1, step("reply late"), step("timeout late"), 150);
+        assert!(answered != unanswered && p0.borrow().is_request_pending(answered));
+        assert_eq!(p0.borrow().get_request(), None);
+        kernel.run(builder.conf()).unwrap();
+
+        let log = RPC_LOG.with(|r| r.borrow().clone());
+        let expected = vec![(150, 0, "timeout late"), (200, 0, "pong from 1"), (200, 0, "reply 1"), (500, 0, "timeout 2")];
+        assert_eq!(log, expected.into_iter().map(|(ts, id, name)| (ts, id, name.to_string())).collect::<Vec<_>>());
+        assert!(!p0.borrow().is_request_pending(answered) && !p0.borrow().is_request_pending(unanswered) && !p0.borrow().is_request_pending(late));
+    }
+
+    #[derive(Default)]
+    struct Suspicions(Vec<(&'static str, Time, ProcessId, ProcessId)>);
+
+    impl simulation::observer::SimulationObserver for Suspicions {
+        fn on_suspect(&mut self, ts: Time, id: ProcessId, suspected: ProcessId) {
+            self.0.push(("suspect", ts, id, suspected));
+        }
+        fn on_restore(&mut self, ts: Time, id: ProcessId, restored: ProcessId) {
+            self.0.push(("restore", ts, id, restored));
+        }
+    }
+
+    #[test]
+    fn test_failure_detector() {
+        let silent_conf = Rc::new(AppConf::new(3, 0, 1, 100_000));
+        let apps = echo_apps(silent_conf);
+        let builder = simulation::SimulationBuilder::new(3)
+            .asynchrony(Box::new(NoAsynchrony::new()))
+            .failure_detector(simulation::FailureDetectorConf::Heartbeat { period: 100, timeout: 250 })
+            .churn("churn:\n  - [0, join, 1.0]\n  - [1000, fail-id, 2]\n  - [2000, recover-id, 2]\n  - [3000, end]");
+        let mut kernel = builder.build(&apps).unwrap();
+        let suspicions = Rc::new(RefCell::new(Suspicions::default()));
+        kernel.add_observer(suspicions.clone());
+        kernel.run_until(0, builder.conf()).unwrap();
+
+        let p0 = kernel.get_process(0).unwrap();
+        p0.borrow().monitor(1);
+        p0.borrow().monitor(2);
+        // the last heartbeat of 2 is the reply to the request sent at 800, which arrives at 1000
+        kernel.run_until(1250, builder.conf()).unwrap();
+        assert!(!p0.borrow().is_suspected(2));
+        kernel.run_until(1300, builder.conf()).unwrap();
+        assert_eq!(p0.borrow().get_suspected(), vec![2]);
+        // the request sent at 1900 arrives after 2 recovers
+        kernel.run_until(2500, builder.conf()).unwrap();
+        assert!(p0.borrow().get_suspected().is_empty());
+        assert_eq!(suspicions.borrow().0, vec![("suspect", 1300, 0, 2), ("restore", 2100, 0, 2)]);
+
+        p0.borrow().unmonitor(1);
+        p0.borrow().unmonitor(2);
+        assert_eq!(kernel.run(builder.conf()).unwrap(), simulation::RunState::End);
+        assert!(p0.borrow().get_failure_detector().get_monitored().is_empty());
+
+        let builder = simulation::SimulationBuilder::new(3)
+            .failure_detector(simulation::FailureDetectorConf::Heartbeat { period: 0, timeout: 250 });
+        assert_eq!(assert_config_error(builder.build(&apps), None), "failure_detector");
+    }
+
+    #[test]
+    fn test_introducers() {
+        let silent_conf = Rc::new(AppConf::new(4, 0, 1, 100_000));
+        let apps = echo_apps(silent_conf);
+        let churn = "churn:\n  - [0, join, 1.0]\n  - [100, fail-id, 2]\n  - [1000, end]";
+        let builder = simulation::SimulationBuilder::new(4)
+            .asynchrony(Box::new(NoAsynchrony::new()))
+            .churn(churn);
+        let mut kernel = builder.build(&apps).unwrap();
+        let p0 = kernel.get_process(0).unwrap();
+        assert_eq!(p0.borrow().get_introducer(), None);
+        kernel.run_until(0, builder.conf()).unwrap();
+        for _ in 0..20 {
+            assert!(matches!(p0.borrow().get_introducer(), Some(1..=3)));
+        }
+
+        let builder = simulation::SimulationBuilder::new(4)
+            .asynchrony(Box::new(NoAsynchrony::new()))
+            .strict(true)
+            .introducers(vec![0, 2])
+            .churn(churn);
+        let mut kernel = builder.build(&apps).unwrap();
+        kernel.run_until(0, builder.conf()).unwrap();
+        let p0 = kernel.get_process(0).unwrap();
+        assert_eq!(p0.borrow().get_introducer(), Some(2));
+        assert_eq!(kernel.get_process(2).unwrap().borrow().get_introducer(), Some(0));
+        kernel.run_until(100, builder.conf()).unwrap();
+        assert_eq!(p0.borrow().get_introducer(), None);
+
+        assert!(simulation::SimulationBuilder::new(4).introducers(vec![4]).build(&apps).is_err());
+        // in strict mode the processes can only learn about others from the introducers
+        assert!(simulation::SimulationBuilder::new(4).strict(true).build(&apps).is_err());
+    }
+
+    #[test]
+    #[should_panic(expected = "unavailable in strict mode")]
+    fn test_strict_mode() {
+        let silent_conf = Rc::new(AppConf::new(2, 0, 1, 100_000));
+        let apps = echo_apps(silent_conf);
+        let builder = simulation::SimulationBuilder::new(2)
+            .asynchrony(Box::new(NoAsynchrony::new()))
+            .strict(true)
+            .introducers(vec![1])
+            .churn("churn:\n  - [0, join, 1.0]\n  - [1000, end]");
+        let mut kernel = builder.build(&apps).unwrap();
+        kernel.run_until(0, builder.conf()).unwrap();
+        kernel.get_process(0).unwrap().borrow().is_process_up(1);
+    }
+
+    #[test]
+    #[cfg(feature = "checkpointing")]
+    fn test_session_churn() {
+        let silent_conf = Rc::new(AppConf::new(10, 0, 1, 100_000));
+        let apps = echo_apps(silent_conf);
+        let churn = "churn:\n  - [0, join, 1.0]\n  - [100, sessions, {processes: \"0..5\", up: {exponential: {lambda: 0.01}}, down: {exponential: {lambda: 0.02}}, until: 5000}]\n  - [6000, end]";
+        let snapshot = snapshot_path("sessions");
+        let builder = simulation::SimulationBuilder::new(10)
+            .asynchrony(Box::new(NoAsynchrony::new()))
+            .churn(churn)
+            .save(2500)
+            .save_filename(snapshot.as_str());
+        let mut kernel = builder.build(&apps).unwrap();
+        let log = record(&mut kernel);
+        kernel.run(builder.conf()).unwrap();
+
+        let log = log.borrow().failures();
+        assert!(log.len() > 50);
+        for id in 0..5 {
+            let changes: Vec<&(Time, String, Vec<ProcessId>)> = log.iter().filter(|c| c.2 == vec![id]).collect();
+            assert!(changes.iter().all(|c| c.0 > 100 && c.0 <= 5000));
+            // the processes start up, so they first fail and then alternate
+            assert!(changes.iter().enumerate().all(|(i, c)| c.1 == if i % 2 == 0 { "fail" } else { "recover" }));
+        }
+        assert!(log.iter().all(|c| c.2[0] < 5));
+        assert_eq!(kernel.get_processes_up().len() + log.iter().filter(|c| c.1 == "fail").count(), 10 + log.iter().filter(|c| c.1 == "recover").count());
+
+        // the sessions continue after a load, without being scheduled again
+        let builder = simulation::SimulationBuilder::new(10)
+            .asynchrony(Box::new(NoAsynchrony::new()))
+            .churn(churn);
+        let (_, log2) = run_loaded(builder, &snapshot);
+        let after_save: Vec<_> = log.iter().filter(|c| c.0 > 2500).cloned().collect();
+        assert_eq!(log2.borrow().failures(), after_save);
+
+        let builder = simulation::SimulationBuilder::new(10)
+            .asynchrony(Box::new(NoAsynchrony::new()))
+            .churn("churn:\n  - [0, join, 1.0]\n  - [100, sessions, {up: {gamma: {shape: 1}}, down: {exponential: {lambda: 0.02}}}]");
+        assert_config_error(builder.build(&apps), Some(3));
+        // the parameters of the distributions are checked, instead of panicking when they are sampled
+        let builder = simulation::SimulationBuilder::new(10)
+            .asynchrony(Box::new(NoAsynchrony::new()))
+            .churn("churn:\n  - [0, join, 1.0]\n  - [100, sessions, {up: {exponential: {lambda: 0}}, down: {exponential: {lambda: 0.02}}, until: 5000}]");
+        assert_config_error(builder.build(&apps), Some(3));
+        let builder = simulation::SimulationBuilder::new(10)
+            .asynchrony(Box::new(NoAsynchrony::new()))
+            .churn("churn:\n  - [0, join, 1.0]\n  - [100, sessions, {up: {pareto: {scale: 100, shape: -1}}, down: {exponential: {lambda: 0.02}}, until: 5000}]");
+        assert_config_error(builder.build(&apps), Some(3));
+        // without until nor an end the simulation would never stop
+        let builder = simulation::SimulationBuilder::new(10)
+            .asynchrony(Box::new(NoAsynchrony::new()))
+            .churn("churn:\n  - [0, join, 1.0]\n  - [100, sessions, {up: {exponential: {lambda: 0.01}}, down: {exponential: {lambda: 0.02}}}]");
+        assert_config_error(builder.build(&apps), Some(3));
+        // processes that did not join are left out of the sessions
+        let builder = simulation::SimulationBuilder::new(10)
+            .asynchrony(Box::new(NoAsynchrony::new()))
+            .churn("churn:\n  - [0, join-id, \"0..5\"]\n  - [100, sessions, {up: {exponential: {lambda: 0.01}}, down: {exponential: {lambda: 0.02}}}]\n  - [5000, end]");
+        let mut kernel = builder.build(&apps).unwrap();
+        let log = record(&mut kernel);
+        kernel.run(builder.conf()).unwrap();
+        let sessions: Vec<ProcessId> = log.borrow().churn.iter().filter(|c| c.0 > 0).flat_map(|c| c.2.clone()).collect();
+        assert!(!sessions.is_empty() && sessions.iter().all(|&id| id < 5));
+    }
+
+    #[test]
+    fn test_trace_churn() {
+        let silent_conf = Rc::new(AppConf::new(4, 0, 1, 100_000));
+        let apps = echo_apps(silent_conf);
+        let builder = simulation::SimulationBuilder::new(4)
+            .asynchrony(Box::new(NoAsynchrony::new()))
+            .churn("churn:\n  - [0, join, 1.0]\n  - [1s, trace, {file: config/test/trace-intervals.txt, time_unit: s, first_process: 1, repeat: true, until: 30000}]\n  - [60s, end]");
+        let mut kernel = builder.build(&apps).unwrap();
+        let log = record(&mut kernel);
+        kernel.run(builder.conf()).unwrap();
+
+        assert_eq!(log.borrow().failures(), vec![(1000, "fail".to_string(), vec![2]), (6000, "recover".to_string(), vec![2]),
+                                                 (11000, "fail".to_string(), vec![1]), (16000, "recover".to_string(), vec![1]),
+                                                 (21000, "fail".to_string(), vec![2]), (26000, "recover".to_string(), vec![2])]);
+        assert_eq!(kernel.get_processes_up().len(), 4);
+
+        let builder = simulation::SimulationBuilder::new(4)
+            .asynchrony(Box::new(NoAsynchrony::new()))
+            .churn("churn:\n  - [0, join, 1.0]\n  - [0, trace, {file: config/test/trace-intervals.txt, first_process: 2}]");
+        assert_eq!(assert_config_error(builder.build(&apps), None), "config/test/trace-intervals.txt");
+        let builder = simulation::SimulationBuilder::new(4)
+            .asynchrony(Box::new(NoAsynchrony::new()))
+            .churn("churn:\n  - [0, join, 1.0]\n  - [0, trace, {format: fta}]");
+        assert_config_error(builder.build(&apps), Some(3));
+    }
+
+    #[test]
+    fn test_domain_churn() {
+        let silent_conf = Rc::new(AppConf::new(8, 0, 1, 100_000));
+        let apps = echo_apps(silent_conf);
+        let builder = simulation::SimulationBuilder::new(8)
+            .asynchrony(Box::new(NoAsynchrony::new()))
+            .domains(simulation::DomainsConf { count: 4, file: None })
+            .churn("churn:\n  - [0, join, 1.0]\n  - [100, fail-domain, 1]\n  - [200, leave-domain, [2, 3]]\n  - [300, recover-domain, 0.5]\n  - [400, fail-domain, 1.0]\n  - [500, end]");
+        let mut kernel = builder.build(&apps).unwrap();
+        let log = record(&mut kernel);
+        kernel.run(builder.conf()).unwrap();
+
+        let log = log.borrow().failures();
+        assert_eq!(log.len(), 3);
+        assert_eq!(log[0], (100, "fail".to_string(), vec![1, 5]));
+        // two of the domains 1, 2 and 3 recover as a whole
+        let recovered = &log[1].2;
+        assert_eq!((log[1].0, recovered.len()), (300, 4));
+        assert!(recovered.iter().all(|&id| id % 4 != 0) && recovered.chunks(2).all(|pair| pair[0] % 4 == pair[1] % 4));
+        let mut failed = log[2].2.clone();
+        failed.sort();
+        let mut expected = recovered.clone();
+        expected.extend(vec![0, 4]);
+        expected.sort();
+        assert_eq!((log[2].0, failed), (400, expected));
+        assert!(kernel.get_processes_up().is_empty());
+        assert_eq!(kernel.get_failure_domains().get_domain(6), Some(2));
+
+        let builder = simulation::SimulationBuilder::new(8)
+            .asynchrony(Box::new(NoAsynchrony::new()))
+            .churn("churn:\n  - [0, join, 1.0]\n  - [100, fail-domain, 1]");
+        assert_config_error(builder.build(&apps), Some(3));
+        let builder = simulation::SimulationBuilder::new(8)
+            .asynchrony(Box::new(NoAsynchrony::new()))
+            .domains(simulation::DomainsConf { count: 4, file: None })
+            .churn("churn:\n  - [0, join, 1.0]\n  - [100, recover-domain, [1, 4]]");
+        assert_config_error(builder.build(&apps), Some(3));
+    }
+
+    #[test]
+    fn test_churn_id_lists() {
+        let silent_conf = Rc::new(AppConf::new(8, 0, 1, 100_000));
+        let apps = echo_apps(silent_conf);
+        let builder = simulation::SimulationBuilder::new(8)
+            .asynchrony(Box::new(NoAsynchrony::new()))
+            .churn("churn:\n  - [0, join-id, [3, 0, 7]]\n  - [100, join-id, \"4..6\"]\n  - [100, join-id, 3]\n  - [200, fail-id, [0, 4]]\n  - [300, recover-id, \"4..5\"]\n  - [400, end]");
+        let mut kernel = builder.build(&apps).unwrap();
+        let log = record(&mut kernel);
+        kernel.run(builder.conf()).unwrap();
+
+        let log = log.borrow();
+        let log: Vec<(Time, &str, Vec<ProcessId>)> = log.churn.iter().map(|(ts, kind, ids)| (*ts, kind.as_str(), ids.clone())).collect();
+        assert_eq!(log, vec![(0, "join", vec![3]), (0, "join", vec![0]), (0, "join", vec![7]),
+                             (100, "join", vec![4]), (100, "join", vec![5]), (100, "join", vec![]),
+                             (200, "fail", vec![0]), (200, "fail", vec![4]), (300, "recover", vec![4])]);
+        let mut up: Vec<ProcessId> = kernel.get_processes_up().iter().map(|p| p.borrow().get_id()).collect();
+        up.sort();
+        assert_eq!(up, vec![3, 4, 5, 7]);
+
+        for churn in &["churn:\n  - [0, join-id, [1, 8]]", "churn:\n  - [0, recover-id, \"6..9\"]", "churn:\n  - [0, fail-id, 0.5]"] {
+            let builder = simulation::SimulationBuilder::new(8)
+                .asynchrony(Box::new(NoAsynchrony::new()))
+                .churn(*churn);
+            assert_config_error(builder.build(&apps), Some(2));
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "checkpointing")]
+    fn test_spawn_and_remove() {
+        let silent_conf = Rc::new(AppConf::new(2, 0, 1, 100_000));
+        let apps = echo_apps(silent_conf.clone());
+        let factory_conf = silent_conf.clone();
+        let factory = simulation::ApplicationFactory::new(move |id| Box::new(Application::new(id, 0, 0, 0, factory_conf.clone())) as Box<dyn ApplicationBase>);
+        let clock = simulation::ClockConf { processes: vec![simulation::clock::ProcessClock { id: 4, offset: 1000, drift: 0.0 }], ..Default::default() };
+        let snapshot = snapshot_path("spawn");
+        let builder = simulation::SimulationBuilder::new(2)
+            .asynchrony(Box::new(NoAsynchrony::new()))
+            .application_factory(factory.clone())
+            .clock(clock)
+            .churn("churn:\n  - [0, join, 1.0]\n  - [100, spawn, 3]\n  - [200, remove-id, [0, 3]]\n  - [300, join-id, 4]\n  - [400, end]")
+            .save(250)
+            .save_filename(snapshot.as_str());
+        let mut kernel = builder.build(&apps).unwrap();
+        let log = record(&mut kernel);
+        kernel.run(builder.conf()).unwrap();
+
+        assert_eq!(log.borrow().churn[1..].to_vec(), vec![(100, "join".to_string(), vec![2, 3, 4]), (200, "leave".to_string(), vec![0]),
+                                                     (200, "leave".to_string(), vec![3]), (300, "join".to_string(), vec![])]);
+        assert_eq!(kernel.get_processes().len(), 5);
+        let up = |kernel: &SimulationKernel| kernel.get_processes_up().iter().map(|p| p.borrow().get_id()).collect::<Vec<ProcessId>>();
+        assert_eq!(up(&kernel), vec![1, 2, 4]);
+        assert!(kernel.get_applications()[3].borrow().as_any().is::<simulation::Tombstone>());
+        assert!(kernel.get_applications()[4].borrow().as_any().is::<Application>());
+        kernel.recover_process(500, 3, builder.conf());
+        assert_eq!(up(&kernel), vec![1, 2, 4]);
+        // the clocks of the spawned processes can be given in the configuration
+        assert_eq!(kernel.get_process(4).unwrap().borrow().get_clock().get_offset(), 1000);
+        assert_eq!(kernel.get_process(2).unwrap().borrow().get_clock().get_offset(), 0);
+
+        // after a load with asynchrony, the spawned processes need op_duration
+        let async_builder = simulation::SimulationBuilder::new(2)
+            .asynchrony(Box::new(UniformAsynchrony::default()))
+            .application_factory(factory.clone())
+            .churn("churn:\n  - [300, spawn, 1]")
+            .load(snapshot.as_str());
+        assert_config_error(async_builder.build(&Vec::new()), Some(2));
+        // the factory is given again after a load, and the ids continue after the spawned processes
+        let builder = simulation::SimulationBuilder::new(2)
+            .asynchrony(Box::new(NoAsynchrony::new()))
+            .application_factory(factory.clone())
+            .churn("churn:\n  - [300, spawn, 1]\n  - [300, fail-id, 5]\n  - [350, recover-id, \"4..6\"]\n  - [400, end]");
+        let (kernel2, _) = run_loaded(builder, &snapshot);
+        assert_eq!(kernel2.get_processes().len(), 6);
+        assert_eq!(up(&kernel2), vec![1, 2, 4, 5]);
+        assert!(kernel2.get_applications()[0].borrow().as_any().is::<simulation::Tombstone>());
+
+        let builder = simulation::SimulationBuilder::new(2)
+            .asynchrony(Box::new(NoAsynchrony::new()))
+            .churn("churn:\n  - [0, join, 1.0]\n  - [100, spawn, 3]");
+        assert_config_error(builder.build(&apps), Some(3));
+        // clocks of processes that are not spawned are rejected
+        let clock = simulation::ClockConf { processes: vec![simulation::clock::ProcessClock { id: 5, offset: 1000, drift: 0.0 }], ..Default::default() };
+        let builder = simulation::SimulationBuilder::new(2)
+            .asynchrony(Box::new(NoAsynchrony::new()))
+            .application_factory(factory)
+            .clock(clock)
+            .churn("churn:\n  - [0, join, 1.0]\n  - [100, spawn, 3]");
+        assert!(builder.build(&apps).is_err());
+    }
+
+    #[test]
+    fn test_stepwise_same_as_run() {
+        let app_conf: Rc<AppConf> = Rc::new(AppConf::new(10, 2, 3, 100));
+        let builder = simulation::SimulationBuilder::new(10)
+            .asynchrony(Box::new(NoAsynchrony::new()))
+            .churn("churn:\n  - [0, join, 1.0]\n  - [1000, end]");
+        let conf = builder.conf();
+
+        let mut kernel = builder.build(&echo_apps(app_conf.clone())).unwrap();
+        assert_eq!(kernel.run(conf).unwrap(), simulation::RunState::End);
+
+        let mut kernel2 = builder.build(&echo_apps(app_conf.clone())).unwrap();
+        assert_eq!(kernel2.step(conf).unwrap(), simulation::RunState::Running);
+        assert_eq!(kernel2.get_events_processed(), 1);
+        assert_eq!(kernel2.get_processes_up().len(), 10);
+
+        assert_eq!(kernel2.run_for(5, conf).unwrap(), simulation::RunState::Running);
+        assert_eq!(kernel2.get_events_processed(), 6);
+
+        assert_eq!(kernel2.run_until(250, conf).unwrap(), simulation::RunState::Running);
+        assert_eq!(kernel2.get_time(), 250);
+        assert!(kernel2.queue.borrow().next_ts().unwrap() > 250);
+
+        assert_eq!(kernel2.run_until(5000, conf).unwrap(), simulation::RunState::End);
+        assert_eq!(kernel2.get_time(), 1000);
+        assert_eq!(kernel2.step(conf).unwrap(), simulation::RunState::End);
+
+        assert_eq!(stats(kernel.get_applications()), stats(kernel2.get_applications()));
+        assert_eq!(kernel.get_events_processed(), kernel2.get_events_processed());
+    }
+
     //////////////////////////////////////////////////////////////////////////////////////////////////
 
     fn serialize_des_yaml<T: Serialize + DeserializeOwned + Debug>(obj: &T) -> T {
@@ -1232,16 +3249,13 @@ mod test {
     #[test]
     #[cfg(feature = "checkpointing")]
     fn test_serde_process() {
-        let conf: Conf = utils::yaml_from_file_to_object("config/conf.yaml");
+        let conf: Conf = utils::yaml_from_file_to_object("config/conf.yaml").unwrap();
 
         let id = 0;
         let asynchrony: Rc<RefCell<Box<Asynchrony>>> = Rc::new(RefCell::new(Box::new(NoAsynchrony::new())));
         let network: Rc<RefCell<Box<Network>>> = Rc::new(RefCell::new(conf.network.clone()));
 
-        let queue = Rc::new(RefCell::new(EventQueue::new()));
-        let rng = Rc::new(RefCell::new(XorShiftRng::seed_from_u64(0)));
-
-        let process = Process::new(id, Rc::new(RefCell::new(0)), 0, asynchrony, network, rng, queue, Rc::new(RefCell::new(Vec::new())));
+        let process = Process::new(id, 0, asynchrony, network, &KernelHandles::default());
 
         serialize_des_yaml(&process);
 
@@ -1259,6 +3273,77 @@ mod test {
 
         serialize_des_bin(&queue);
     }
+
+    fn pop_targets(mut queue: EventQueue) -> Vec<ProcessId> {
+        let mut targets = Vec::new();
+        while let Some(event) = queue.next_event() {
+            targets.push(event.target);
+        }
+        targets
+    }
+
+    fn same_ts_queue(tie_breaking: TieBreaking) -> EventQueue {
+        let mut queue = EventQueue::with_tie_breaking(tie_breaking);
+        queue.add_event(Event::new_end(10));
+        for target in 1..=20 {
+            queue.add_event(Event::new(10, target, Rc::new(simulation::Kernel), EventKind::Message));
+        }
+        queue.add_event(Event::new(5, 21, Rc::new(simulation::Kernel), EventKind::Message));
+        queue
+    }
+
+    #[test]
+    fn test_tie_breaking() {
+        let fifo = pop_targets(same_ts_queue(TieBreaking::Fifo));
+        let mut expected = vec![21];
+        expected.extend(1..=20);
+        expected.push(0);
+        assert_eq!(fifo, expected);
+
+        let lifo = pop_targets(same_ts_queue(TieBreaking::Lifo));
+        let mut expected = vec![21];
+        expected.extend((1..=20).rev());
+        expected.push(0);
+        assert_eq!(lifo, expected);
+
+        let random = pop_targets(same_ts_queue(TieBreaking::Random(3)));
+        assert_eq!(random, pop_targets(same_ts_queue(TieBreaking::Random(3))));
+        assert_ne!(random, pop_targets(same_ts_queue(TieBreaking::Random(4))));
+        assert_ne!(random, fifo);
+        assert_eq!((random[0], random[21]), (21, 0));
+        let mut sorted = random.clone();
+        sorted.sort();
+        assert_eq!(sorted, (0..=21).collect::<Vec<ProcessId>>());
+
+        assert_eq!(serde_yaml::from_str::<TieBreaking>("lifo").unwrap(), TieBreaking::Lifo);
+        assert_eq!(serde_yaml::from_str::<TieBreaking>("random: 3").unwrap(), TieBreaking::Random(3));
+    }
+
+    #[test]
+    #[cfg(feature = "checkpointing")]
+    fn test_load_keeps_tie_breaking() {
+        let apps = echo_apps(Rc::new(AppConf::new(3, 1, 3, 100)));
+        let snapshot = snapshot_path("tie-breaking");
+        let builder = simulation::SimulationBuilder::new(3)
+            .asynchrony(Box::new(NoAsynchrony::new()))
+            .tie_breaking(TieBreaking::Lifo)
+            .churn("churn:\n  - [0, join, 1.0]\n  - [1000, end]")
+            .save(150)
+            .save_filename(snapshot.as_str());
+        builder.build(&apps).unwrap().run(builder.conf()).unwrap();
+
+        let load = |tie_breaking| simulation::SimulationBuilder::new(3)
+            .asynchrony(Box::new(NoAsynchrony::new()))
+            .tie_breaking(tie_breaking)
+            .churn("churn:\n  - [1000, end]")
+            .load(snapshot.as_str())
+            .build(&Vec::new());
+        let fifo = load(TieBreaking::Fifo);
+        let lifo = load(TieBreaking::Lifo);
+        let _ = fs::remove_file(&snapshot);
+        assert!(matches!(fifo, Err(Error::Simulation(_))));
+        assert!(lifo.is_ok());
+    }
     
     fn assert_same_executed(executed1: &Vec<(Time, ProcessId, ProcessId)>, executed2: &Vec<(Time, ProcessId, ProcessId)>) -> bool {
         if executed1.len() != executed2.len() {
@@ -1291,24 +3376,24 @@ mod test {
 
     #[cfg(feature = "checkpointing")]
     fn repeat_same_experiment(conf_filename: &str) {
-        let app_conf: Rc<AppConf> = Rc::new(utils::yaml_from_file_to_object(&conf_filename));
+        let app_conf: Rc<AppConf> = Rc::new(utils::yaml_from_file_to_object(&conf_filename).unwrap());
 
         let mut apps = Vec::new();
         for i in 0..app_conf.n {
             apps.push(Rc::new(RefCell::new(Box::new(Application::new(i, 0, 0, 0, app_conf.clone())) as Box<dyn ApplicationBase>)));
         }
-        let kernel = SimulationKernel::init(&apps, conf_filename);
+        let kernel = SimulationKernel::init(&apps, conf_filename).unwrap();
 
         let stats_run1 = stats(&kernel.get_applications());
 
 
-        let app_conf2: Rc<AppConf> = Rc::new(utils::yaml_from_file_to_object(&conf_filename));
+        let app_conf2: Rc<AppConf> = Rc::new(utils::yaml_from_file_to_object(&conf_filename).unwrap());
 
         let mut apps2 = Vec::new();
         for i in 0..app_conf2.n {
             apps2.push(Rc::new(RefCell::new(Box::new(Application::new(i, 0, 0, 0, app_conf2.clone())) as Box<dyn ApplicationBase>)));
         }
-        let kernel2 = SimulationKernel::init(&apps2, conf_filename);
+        let kernel2 = SimulationKernel::init(&apps2, conf_filename).unwrap();
 
         let stats_run2 = stats(&kernel2.get_applications());
 
@@ -1334,13 +3419,13 @@ mod test {
 
     #[cfg(feature = "checkpointing")]
     fn checkpointing_same_rng(conf_filename: &str) {
-        let app_conf: Rc<AppConf> = Rc::new(utils::yaml_from_file_to_object(&conf_filename));
+        let app_conf: Rc<AppConf> = Rc::new(utils::yaml_from_file_to_object(&conf_filename).unwrap());
 
         let mut apps = Vec::new();
         for i in 0..app_conf.n {
             apps.push(Rc::new(RefCell::new(Box::new(Application::new(i, 0, 0, 0, app_conf.clone())) as Box<dyn ApplicationBase>)));
         }
-        let kernel = SimulationKernel::init(&apps, conf_filename);
+        let kernel = SimulationKernel::init(&apps, conf_filename).unwrap();
 
         let stats_run1 = stats(&kernel.get_applications());
 
@@ -1350,7 +3435,7 @@ mod test {
         //let app_conf2: Rc<AppConf> = Rc::new(utils::yaml_from_file_to_object(&conf_filename2));
 
         let apps2 = Vec::new();
-        let kernel2 = SimulationKernel::init(&apps2, conf_filename2);
+        let kernel2 = SimulationKernel::init(&apps2, conf_filename2).unwrap();
 
         let stats_run2 = stats(&kernel2.get_applications());
 
@@ -1376,13 +3461,13 @@ mod test {
 
     #[cfg(feature = "checkpointing")]
     fn checkpointing_different_rng(conf_filename: &str) {
-        let app_conf: Rc<AppConf> = Rc::new(utils::yaml_from_file_to_object(&conf_filename));
+        let app_conf: Rc<AppConf> = Rc::new(utils::yaml_from_file_to_object(&conf_filename).unwrap());
 
         let mut apps = Vec::new();
         for i in 0..app_conf.n {
             apps.push(Rc::new(RefCell::new(Box::new(Application::new(i, 0, 0, 0, app_conf.clone())) as Box<dyn ApplicationBase>)));
         }
-        let kernel = SimulationKernel::init(&apps, conf_filename);
+        let kernel = SimulationKernel::init(&apps, conf_filename).unwrap();
 
         stats(&kernel.get_applications());
 
@@ -1392,7 +3477,7 @@ mod test {
         //let app_conf2: Rc<AppConf> = Rc::new(utils::yaml_from_file_to_object(&conf_filename2));
 
         let apps2 = Vec::new();
-        let kernel2 = SimulationKernel::init(&apps2, conf_filename2);
+        let kernel2 = SimulationKernel::init(&apps2, conf_filename2).unwrap();
 
         stats(&kernel2.get_applications());
 
@@ -1417,27 +3502,27 @@ mod test {
 
     #[cfg(feature = "checkpointing")]
     fn checkpointing_different_rng2(conf_filename: &str) {
-        let conf: Conf = utils::yaml_from_file_to_object(&conf_filename);
-        let app_conf: Rc<AppConf> = Rc::new(utils::yaml_from_file_to_object(&conf_filename));
+        let conf: Conf = utils::yaml_from_file_to_object(&conf_filename).unwrap();
+        let app_conf: Rc<AppConf> = Rc::new(utils::yaml_from_file_to_object(&conf_filename).unwrap());
 
         let mut apps = Vec::new();
         for i in 0..app_conf.n {
             apps.push(Rc::new(RefCell::new(Box::new(Application::new(i, 0, 0, 0, app_conf.clone())) as Box<dyn ApplicationBase>)));
         }
-        let kernel = SimulationKernel::init(&apps, conf_filename);
+        let kernel = SimulationKernel::init(&apps, conf_filename).unwrap();
 
         stats(&kernel.get_applications());
 
 
-        let mut conf2: Conf = utils::yaml_from_file_to_object("config/test/conf-load-new_rng.yaml");
+        let mut conf2: Conf = utils::yaml_from_file_to_object("config/test/conf-load-new_rng.yaml").unwrap();
         conf2.new_seed = Some(4370871 as u64);
         assert_ne!(&conf2.load, &None);
         if let Some(load_filename) = &conf2.load {
             assert_eq!(&conf.save_filename, load_filename);
         }
-        let mut kernel2 = SimulationKernel::load_state(&conf2);
+        let mut kernel2 = SimulationKernel::load_state(&conf2).unwrap();
 
-        kernel2.run(&conf2);
+        kernel2.run(&conf2).unwrap();
 
         stats(&kernel2.get_applications());
 
@@ -7,8 +7,10 @@ use std::fs::File;
 use std::io::{BufRead, BufReader};
 
 use std::path::Path;
+use std::collections::{BTreeSet, HashMap};
 use std::fmt::{Debug};
 
+use rand::SeedableRng;
 use rand::distributions::{Distribution, Uniform};
 use rand_xorshift::XorShiftRng;
 
@@ -19,13 +21,41 @@ use serde::de::{self, Deserialize, Deserializer, Visitor, SeqAccess, MapAccess};
 use std::rc::Rc;
 use std::cell::RefCell;
 
-use crate::simulation::wrappers::{UniformWrapper, LogNormalWrapper, DistributionWrapper};
+use crate::simulation::wrappers::{UniformWrapper, NormalWrapper, LogNormalWrapper, DistributionWrapper};
 use crate::simulation::Time;
+use crate::simulation::TimeUnit;
 use crate::simulation::ProcessId;
+use crate::simulation::utils;
+use crate::simulation::domains::FailureDomains;
+use crate::Error;
 
 #[typetag::serde(tag = "type")]
 pub trait Network: Debug + objekt::Clone {
     fn get_latency(&mut self, rng: Rc<RefCell<XorShiftRng>>, sender: ProcessId, target: ProcessId) -> Option<Time>;
+
+    /// called before the simulation starts with the unit of the simulation time,
+    /// so that latencies given in another unit can be converted to it
+    fn set_time_unit(&mut self, _unit: TimeUnit) {}
+
+    /// copies of a message from sender to target that reach target, which is empty when the message is lost
+    /// by default the message is delivered once, with the latency given by get_latency
+    fn get_deliveries(&mut self, rng: Rc<RefCell<XorShiftRng>>, sender: ProcessId, target: ProcessId) -> Vec<Delivery> {
+        self.get_latency(rng, sender, target).map(Delivery::new).into_iter().collect()
+    }
+}
+
+/// a copy of a message that reaches its target after latency
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Delivery {
+    pub latency: Time,
+    /// the payload was damaged by the network
+    pub corrupted: bool
+}
+
+impl Delivery {
+    pub fn new(latency: Time) -> Self {
+        Delivery { latency, corrupted: false }
+    }
 }
 
 clone_trait_object!(Network);
@@ -70,7 +100,13 @@ impl Default for ConstantNetwork {
 #[derive(Serialize)]
 #[derive(Debug, Clone)]
 pub struct MatrixNetwork {
+    /// when directed latency_matrix[sender][target] is the latency from sender to target,
+    /// otherwise only the lower triangle is kept, i.e. latency_matrix[max(sender, target)][min(sender, target)]
     latency_matrix: Vec<Vec<Time>>,
+    directed: bool,
+    /// when the latency file has a unit, the latencies are kept in nanoseconds until set_time_unit converts them
+    #[serde(skip_serializing)]
+    latency_unit: Option<TimeUnit>,
     jitter: Box<Jitter>,
     loss: f64,
     #[serde(skip, default = "uniform_default")]
@@ -81,22 +117,48 @@ impl MatrixNetwork {
     /// assumes file with the following structure:
     /// sender node, target node, latency, each separated by '\t',
     /// where the identifiers of the nodes go from 0 to num_nodes-1
-    pub fn new<P: AsRef<Path>>(filename: P, n: ProcessId, jitter: Box<Jitter>, loss: f64) -> Self {
-        let mut latency_matrix: Vec<Vec<Time>> = (0..n).map(|x| vec![0; (x+1) as usize]).collect();
+    /// and the latencies are in the unit of the simulation time
+    /// latencies are symmetric, so when the file has both directions of a link the one that comes last is used
+    pub fn new<P: AsRef<Path>>(filename: P, n: ProcessId, jitter: Box<dyn Jitter>, loss: f64) -> Result<Self, Error> {
+        MatrixNetwork::new_with_options(filename, n, None, false, jitter, loss)
+    }
+    /// same as new, but the latencies in the file are in latency_unit, when it is given,
+    /// and are converted to the unit of the simulation time when the simulation starts
+    /// when directed the latency from sender to target is the one in the line that starts with sender, target,
+    /// and if the file has only one direction of a link it is used for both directions
+    pub fn new_with_options<P: AsRef<Path>>(filename: P, n: ProcessId, latency_unit: Option<TimeUnit>, directed: bool, jitter: Box<dyn Jitter>, loss: f64) -> Result<Self, Error> {
+        // latencies missing from a directed file are marked with UNSET
+        const UNSET: Time = -1;
+        let mut latency_matrix: Vec<Vec<Time>> = if directed {
+            (0..n).map(|_| vec![UNSET; n as usize]).collect()
+        } else {
+            (0..n).map(|x| vec![0; (x+1) as usize]).collect()
+        };
 
+        let file_display = filename.as_ref().display().to_string();
         let mut num_nodes_file = 0;
-        let file = File::open(filename).unwrap();
-        for line_result in BufReader::new(file).lines() {
-            let line = line_result.unwrap();
+        let file = File::open(&filename).map_err(|source| Error::Io { file: file_display.clone(), source })?;
+        for (line_index, line_result) in BufReader::new(file).lines().enumerate() {
+            let line = line_result.map_err(|source| Error::Io { file: file_display.clone(), source })?;
+            let error = |msg: &str| Error::config(file_display.clone(), Some(line_index + 1), msg);
             let content = line.split('\t').collect::<Vec<&str>>();
+            if content.len() < 3 {
+                return Err(error("expected sender node, target node and latency separated by '\\t'"));
+            }
 
-            let i = content[0].parse::<ProcessId>().unwrap();
-            let j = content[1].parse::<ProcessId>().unwrap();
-            let latency_float = content[2].parse::<f32>().unwrap().round();
-            let latency = latency_float as Time;
+            let i = content[0].trim().parse::<ProcessId>().map_err(|_| error("sender node must be a non-negative integer"))?;
+            let j = content[1].trim().parse::<ProcessId>().map_err(|_| error("target node must be a non-negative integer"))?;
+            let latency_float = content[2].trim().parse::<f64>().map_err(|_| error("latency must be a number"))?;
+            if latency_float < 0.0 {
+                return Err(error("latency must not be negative"));
+            }
+            let latency = match latency_unit {
+                Some(unit) => unit.convert(latency_float, TimeUnit::Ns),
+                None => latency_float.round() as Time
+            };
 
             if i < n && j < n {
-                MatrixNetwork::set_latency(&mut latency_matrix, i, j, latency);
+                MatrixNetwork::set_latency(&mut latency_matrix, directed, i, j, latency);
             }
 
             if i > num_nodes_file {
@@ -107,52 +169,90 @@ impl MatrixNetwork {
             }
         }
         num_nodes_file += 1;
+        if num_nodes_file == 1 && n > 1 {
+            return Err(Error::config(file_display, None, "the latency file must have latencies for at least two nodes"));
+        }
+
+        if directed {
+            for i in 0..n {
+                for j in 0..n {
+                    if i == j {
+                        latency_matrix[i as usize][j as usize] = 0;
+                    } else if latency_matrix[i as usize][j as usize] == UNSET {
+                        latency_matrix[i as usize][j as usize] = latency_matrix[j as usize][i as usize].max(0);
+                    }
+                }
+            }
+        }
 
         //in case n > nodes in file
         for i in num_nodes_file..n {
+            let base = i % num_nodes_file;
+            let mut latencies_sum: Time = 0;
             for j in 0..i {
-                let latency = MatrixNetwork::get_latency(&mut latency_matrix, i % num_nodes_file, j);
-                MatrixNetwork::set_latency(&mut latency_matrix, i, j, latency);
+                let latency = MatrixNetwork::get_latency(&latency_matrix, directed, base, j);
+                MatrixNetwork::set_latency(&mut latency_matrix, directed, i, j, latency);
+                if directed {
+                    let latency = MatrixNetwork::get_latency(&latency_matrix, directed, j, base);
+                    MatrixNetwork::set_latency(&mut latency_matrix, directed, j, i, latency);
+                }
+                latencies_sum += MatrixNetwork::get_latency(&latency_matrix, directed, i, j);
             }
 
-            let latencies_sum: Time = latency_matrix[i as usize].iter().sum();
-            let average_latency = latencies_sum as f32 / (i - 1) as f32;
-            MatrixNetwork::set_latency(&mut latency_matrix, i, i % num_nodes_file, average_latency.round() as Time);
+            let average_latency = latencies_sum as f64 / (i - 1) as f64;
+            MatrixNetwork::set_latency(&mut latency_matrix, directed, i, base, average_latency.round() as Time);
+            if directed {
+                let latencies_sum: Time = (0..i).map(|j| MatrixNetwork::get_latency(&latency_matrix, directed, j, i)).sum();
+                let average_latency = latencies_sum as f64 / (i - 1) as f64;
+                MatrixNetwork::set_latency(&mut latency_matrix, directed, base, i, average_latency.round() as Time);
+            }
         }
 
-        MatrixNetwork { latency_matrix, jitter, loss, uniform: Uniform::new(0.0, 1.0) }
+        Ok(MatrixNetwork { latency_matrix, directed, latency_unit, jitter, loss, uniform: Uniform::new(0.0, 1.0) })
     }
-    fn new_from_matrix(latency_matrix: Vec<Vec<Time>>, jitter: Box<Jitter>, loss: f64) -> Self {
-        MatrixNetwork { latency_matrix, jitter, loss, uniform: Uniform::new(0.0, 1.0) }
+    fn new_from_matrix(latency_matrix: Vec<Vec<Time>>, directed: bool, jitter: Box<dyn Jitter>, loss: f64) -> Self {
+        MatrixNetwork { latency_matrix, directed, latency_unit: None, jitter, loss, uniform: Uniform::new(0.0, 1.0) }
     }
-    fn set_latency(latency_matrix: &mut Vec<Vec<Time>>, sender: ProcessId, target: ProcessId, latency: Time) {
-        if sender > target {
-            latency_matrix[sender as usize][target as usize] = latency;
+    fn index(directed: bool, sender: ProcessId, target: ProcessId) -> (usize, usize) {
+        if directed || sender > target {
+            (sender as usize, target as usize)
         } else {
-            latency_matrix[target as usize][sender as usize] = latency;
+            (target as usize, sender as usize)
         }
     }
-    fn get_latency(latency_matrix: &mut Vec<Vec<Time>>, sender: ProcessId, target: ProcessId) -> Time {
-        if sender > target {
-            latency_matrix[sender as usize][target as usize]
+    fn set_latency(latency_matrix: &mut [Vec<Time>], directed: bool, sender: ProcessId, target: ProcessId, latency: Time) {
+        let (i, j) = MatrixNetwork::index(directed, sender, target);
+        latency_matrix[i][j] = latency;
+    }
+    fn get_latency(latency_matrix: &[Vec<Time>], directed: bool, sender: ProcessId, target: ProcessId) -> Time {
+        let (i, j) = MatrixNetwork::index(directed, sender, target);
+        latency_matrix[i][j]
+    }
+    /// applies the loss and the jitter of the network to latency
+    fn sample(&mut self, rng: Rc<RefCell<XorShiftRng>>, latency: Time) -> Option<Time> {
+        if self.uniform.sample(&mut *rng.borrow_mut()) < self.loss {
+            None
         } else {
-            latency_matrix[target as usize][sender as usize]
+            Some(latency + self.jitter.get_jitter(rng.clone(), latency))
         }
     }
+    fn len(&self) -> ProcessId {
+        self.latency_matrix.len() as ProcessId
+    }
 }
 
 #[typetag::serde]
 impl Network for MatrixNetwork {
     fn get_latency(&mut self, rng: Rc<RefCell<XorShiftRng>>, sender: ProcessId, target: ProcessId) -> Option<Time> {
-        if self.uniform.sample(&mut *rng.borrow_mut()) < self.loss {
-            None
-        } else {
-            if sender > target {
-                let latency = self.latency_matrix[sender as usize][target as usize];
-                Some(latency + self.jitter.get_jitter(rng.clone(), latency))
-            } else {
-                let latency = self.latency_matrix[target as usize][sender as usize];
-                Some(latency + self.jitter.get_jitter(rng.clone(), latency))
+        let latency = MatrixNetwork::get_latency(&self.latency_matrix, self.directed, sender, target);
+        self.sample(rng, latency)
+    }
+    fn set_time_unit(&mut self, unit: TimeUnit) {
+        if self.latency_unit.take().is_some() {
+            for latencies in self.latency_matrix.iter_mut() {
+                for latency in latencies.iter_mut() {
+                    *latency = TimeUnit::Ns.convert(*latency as f64, unit);
+                }
             }
         }
     }
@@ -166,7 +266,7 @@ impl<'de> Deserialize<'de> for MatrixNetwork {
         #[derive(Deserialize)]
         #[allow(non_camel_case_types)]
         #[serde(field_identifier, rename_all = "lowercase")]
-        enum Field { Latency_File, N, Latency_Matrix, Jitter, Loss };
+        enum Field { Latency_File, Latency_Unit, N, Latency_Matrix, Directed, Jitter, Loss };
 
         struct MatrixNetworkVisitor;
 
@@ -181,25 +281,16 @@ impl<'de> Deserialize<'de> for MatrixNetwork {
                 where
                     V: SeqAccess<'de>,
             {
-                if let Some(3) = seq.size_hint() {
-                    let latency_matrix = seq.next_element()?
-                        .ok_or_else(|| de::Error::invalid_length(0, &self))?;
-                    let jitter = seq.next_element()?
-                        .ok_or_else(|| de::Error::invalid_length(1, &self))?;
-                    let loss = seq.next_element()?
-                        .ok_or_else(|| de::Error::invalid_length(2, &self))?;
-                    Ok(MatrixNetwork::new_from_matrix(latency_matrix, jitter, loss))
-                } else {
-                    let latency_file: String = seq.next_element()?
-                        .ok_or_else(|| de::Error::invalid_length(0, &self))?;
-                    let n = seq.next_element()?
-                        .ok_or_else(|| de::Error::invalid_length(1, &self))?;
-                    let jitter = seq.next_element()?
-                        .ok_or_else(|| de::Error::invalid_length(2, &self))?;
-                    let loss = seq.next_element()?
-                        .ok_or_else(|| de::Error::invalid_length(3, &self))?;
-                    Ok(MatrixNetwork::new(latency_file, n, jitter, loss))
-                }
+                // the fields in the order they are serialized, as in FIELDS
+                let latency_matrix = seq.next_element()?
+                    .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+                let directed = seq.next_element()?
+                    .ok_or_else(|| de::Error::invalid_length(1, &self))?;
+                let jitter = seq.next_element()?
+                    .ok_or_else(|| de::Error::invalid_length(2, &self))?;
+                let loss = seq.next_element()?
+                    .ok_or_else(|| de::Error::invalid_length(3, &self))?;
+                Ok(MatrixNetwork::new_from_matrix(latency_matrix, directed, jitter, loss))
             }
 
             fn visit_map<V>(self, mut map: V) -> Result<MatrixNetwork, V::Error>
@@ -207,8 +298,10 @@ impl<'de> Deserialize<'de> for MatrixNetwork {
                     V: MapAccess<'de>,
             {
                 let mut latency_file = None;
+                let mut latency_unit = None;
                 let mut n = None;
                 let mut latency_matrix = None;
+                let mut directed = None;
                 let mut jitter = None;
                 let mut loss = None;
                 while let Some(key) = map.next_key()? {
@@ -219,6 +312,12 @@ impl<'de> Deserialize<'de> for MatrixNetwork {
                             }
                             latency_file = Some(map.next_value()?);
                         },
+                        Field::Latency_Unit => {
+                            if latency_unit.is_some() {
+                                return Err(de::Error::duplicate_field("latency_unit"));
+                            }
+                            latency_unit = Some(map.next_value()?);
+                        },
                         Field::N => {
                             if n.is_some() {
                                 return Err(de::Error::duplicate_field("n"));
@@ -231,6 +330,12 @@ impl<'de> Deserialize<'de> for MatrixNetwork {
                             }
                             latency_matrix = Some(map.next_value()?);
                         },
+                        Field::Directed => {
+                            if directed.is_some() {
+                                return Err(de::Error::duplicate_field("directed"));
+                            }
+                            directed = Some(map.next_value()?);
+                        },
                         Field::Jitter => {
                             if jitter.is_some() {
                                 return Err(de::Error::duplicate_field("jitter"));
@@ -248,25 +353,143 @@ impl<'de> Deserialize<'de> for MatrixNetwork {
 
                 let jitter = jitter.ok_or_else(|| de::Error::missing_field("jitter"))?;
                 let loss = loss.ok_or_else(|| de::Error::missing_field("loss"))?;
+                let directed = directed.unwrap_or(false);
 
                 if latency_file == None && latency_matrix != None {
                     let latency_matrix = latency_matrix.ok_or_else(|| de::Error::missing_field("latency_matrix"))?;
 
-                    return Ok(MatrixNetwork::new_from_matrix(latency_matrix, jitter, loss));
+                    return Ok(MatrixNetwork::new_from_matrix(latency_matrix, directed, jitter, loss));
                 } else {
                     let latency_file: String = latency_file.ok_or_else(|| de::Error::missing_field("latency_file"))?;
                     let n = n.ok_or_else(|| de::Error::missing_field("n"))?;
 
-                    return Ok(MatrixNetwork::new(latency_file, n, jitter, loss));
+                    return MatrixNetwork::new_with_options(latency_file, n, latency_unit, directed, jitter, loss).map_err(de::Error::custom);
                 }
             }
         }
 
-        const FIELDS: &'static [&'static str] = &["mean", "std_dev"];
+        const FIELDS: &'static [&'static str] = &["latency_matrix", "directed", "jitter", "loss"];
         deserializer.deserialize_struct("MatrixNetwork", FIELDS, MatrixNetworkVisitor)
     }
 }
 
+/// Network where each process belongs to a site, for e.g. a datacenter or a node of a latency dataset,
+/// and the latency between two processes is the latency between their sites,
+/// which needs memory O(sites^2) plus O(n) only when the sites of the processes are read from a file.
+/// Processes in the same site have local_latency between them.
+#[derive(Serialize)]
+#[derive(Debug, Clone)]
+pub struct SiteNetwork {
+    /// latencies between sites, where n is the number of sites
+    sites: MatrixNetwork,
+    /// site of each process, processes that are not in it are in site id % number of sites
+    process_sites: Vec<ProcessId>,
+    local_latency: Time
+}
+
+/// group of the processes that are not in a groups file
+pub const NO_GROUP: ProcessId = ProcessId::MAX;
+
+impl SiteNetwork {
+    /// process id is in site id % number of sites
+    pub fn new(sites: MatrixNetwork, local_latency: Time) -> Self {
+        SiteNetwork { sites, process_sites: Vec::new(), local_latency }
+    }
+    /// reads the site of each process from sites_file, see read_process_groups for its structure
+    pub fn new_from_file<P: AsRef<Path>>(sites: MatrixNetwork, sites_file: P, local_latency: Time) -> Result<Self, Error> {
+        let process_sites = read_process_groups(sites_file, sites.len())?;
+        Ok(SiteNetwork { sites, process_sites, local_latency })
+    }
+    /// places each process in the site with the id of its failure domain, so there must be one site per domain
+    pub fn new_from_domains(sites: MatrixNetwork, domains: &FailureDomains, local_latency: Time) -> Result<Self, Error> {
+        if domains.len() != sites.len() {
+            return Err(Error::Simulation(format!("there are {} failure domains but {} sites, there must be one site per domain", domains.len(), sites.len())));
+        }
+        Ok(SiteNetwork { sites, process_sites: domains.get_process_domains().to_vec(), local_latency })
+    }
+    pub fn get_site(&self, id: ProcessId) -> ProcessId {
+        match self.process_sites.get(id as usize) {
+            Some(&site) if site != NO_GROUP => site,
+            _ => id % self.sites.len()
+        }
+    }
+}
+
+/// assumes file with the following structure:
+/// process, group, each separated by '\t',
+/// where groups go from 0 to num_groups-1
+/// returns the group of each process, indexed by process id, where processes that are not in the file have NO_GROUP
+pub fn read_process_groups<P: AsRef<Path>>(filename: P, num_groups: ProcessId) -> Result<Vec<ProcessId>, Error> {
+    let file_display = filename.as_ref().display().to_string();
+    let file = File::open(&filename).map_err(|source| Error::Io { file: file_display.clone(), source })?;
+
+    let mut groups = Vec::new();
+    for (line_index, line_result) in BufReader::new(file).lines().enumerate() {
+        let line = line_result.map_err(|source| Error::Io { file: file_display.clone(), source })?;
+        let error = |msg: String| Error::config(file_display.clone(), Some(line_index + 1), msg);
+        if line.trim().is_empty() {
+            continue;
+        }
+        let content = line.split('\t').collect::<Vec<&str>>();
+        if content.len() < 2 {
+            return Err(error("expected process and group separated by '\\t'".to_string()));
+        }
+
+        let id = content[0].trim().parse::<ProcessId>().map_err(|_| error("process must be a non-negative integer".to_string()))?;
+        let group = content[1].trim().parse::<ProcessId>().map_err(|_| error("group must be a non-negative integer".to_string()))?;
+        if group >= num_groups {
+            return Err(error(format!("group {} does not exist, groups must be in 0..{}", group, num_groups)));
+        }
+
+        if id as usize >= groups.len() {
+            groups.resize(id as usize + 1, NO_GROUP);
+        }
+        groups[id as usize] = group;
+    }
+    Ok(groups)
+}
+
+#[typetag::serde]
+impl Network for SiteNetwork {
+    fn get_latency(&mut self, rng: Rc<RefCell<XorShiftRng>>, sender: ProcessId, target: ProcessId) -> Option<Time> {
+        let sender_site = self.get_site(sender);
+        let target_site = self.get_site(target);
+        let latency = if sender_site == target_site {
+            self.local_latency
+        } else {
+            MatrixNetwork::get_latency(&self.sites.latency_matrix, self.sites.directed, sender_site, target_site)
+        };
+        self.sites.sample(rng, latency)
+    }
+    fn set_time_unit(&mut self, unit: TimeUnit) {
+        self.sites.set_time_unit(unit);
+    }
+}
+
+impl<'de> Deserialize<'de> for SiteNetwork {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: Deserializer<'de>,
+    {
+        /// either the configuration, with sites_file, or a snapshot, with process_sites
+        #[derive(Deserialize)]
+        struct SiteNetworkFields {
+            sites: MatrixNetwork,
+            sites_file: Option<String>,
+            #[serde(default)]
+            process_sites: Vec<ProcessId>,
+            #[serde(default)]
+            local_latency: Time
+        }
+
+        let fields = SiteNetworkFields::deserialize(deserializer)?;
+        match fields.sites_file {
+            Some(sites_file) => SiteNetwork::new_from_file(fields.sites, sites_file, fields.local_latency).map_err(de::Error::custom),
+            None => Ok(SiteNetwork { sites: fields.sites, process_sites: fields.process_sites, local_latency: fields.local_latency })
+        }
+    }
+}
+
 
 #[typetag::serde(tag = "type")]
 pub trait Jitter: Debug + objekt::Clone {
@@ -346,6 +569,441 @@ impl Default for LogNormalJitter {
     }
 }
 
+/// Decides which messages are lost, independently of the latency given by the network.
+/// A loss model is combined with any network through LossyNetwork.
+#[typetag::serde(tag = "type")]
+pub trait Loss: Debug + objekt::Clone {
+    fn is_lost(&mut self, rng: Rc<RefCell<XorShiftRng>>, sender: ProcessId, target: ProcessId) -> bool;
+}
+
+clone_trait_object!(Loss);
+
+#[derive(Serialize, Deserialize)]
+#[derive(Debug, Clone)]
+pub struct NoLoss;
+
+#[typetag::serde]
+impl Loss for NoLoss {
+    fn is_lost(&mut self, _rng: Rc<RefCell<XorShiftRng>>, _sender: ProcessId, _target: ProcessId) -> bool {
+        false
+    }
+}
+
+/// each message is lost independently with probability loss
+#[derive(Serialize, Deserialize)]
+#[derive(Debug, Clone)]
+pub struct BernoulliLoss {
+    loss: f64,
+    #[serde(skip, default = "uniform_default")]
+    uniform: Uniform<f64>
+}
+
+impl BernoulliLoss {
+    pub fn new(loss: f64) -> Self {
+        BernoulliLoss { loss, uniform: Uniform::new(0.0, 1.0) }
+    }
+}
+
+#[typetag::serde]
+impl Loss for BernoulliLoss {
+    fn is_lost(&mut self, rng: Rc<RefCell<XorShiftRng>>, _sender: ProcessId, _target: ProcessId) -> bool {
+        self.uniform.sample(&mut *rng.borrow_mut()) < self.loss
+    }
+}
+
+/// Two-state Markov loss (Gilbert-Elliott), which loses messages in bursts.
+/// Each link, from sender to target, is either in the good or in the bad state. Before each message
+/// the link moves from good to bad with probability good_to_bad and from bad to good with probability bad_to_good,
+/// and then the message is lost with probability good_loss or bad_loss, depending on the state of the link.
+/// All links start in the good state.
+#[derive(Serialize, Deserialize)]
+#[derive(Debug, Clone)]
+pub struct GilbertElliottLoss {
+    good_to_bad: f64,
+    bad_to_good: f64,
+    #[serde(default)]
+    good_loss: f64,
+    #[serde(default = "bad_loss_default")]
+    bad_loss: f64,
+    /// links in the bad state, saved in snapshots so that bursts continue after a load
+    #[serde(default)]
+    bad_links: BTreeSet<(ProcessId, ProcessId)>,
+    #[serde(skip, default = "uniform_default")]
+    uniform: Uniform<f64>
+}
+
+fn bad_loss_default() -> f64 {
+    1.0
+}
+
+impl GilbertElliottLoss {
+    pub fn new(good_to_bad: f64, bad_to_good: f64, good_loss: f64, bad_loss: f64) -> Self {
+        GilbertElliottLoss { good_to_bad, bad_to_good, good_loss, bad_loss, bad_links: BTreeSet::new(), uniform: Uniform::new(0.0, 1.0) }
+    }
+    /// fraction of messages lost in the long run
+    pub fn mean_loss(&self) -> f64 {
+        let transitions = self.good_to_bad + self.bad_to_good;
+        if transitions == 0.0 {
+            return self.good_loss;
+        }
+        (self.bad_to_good * self.good_loss + self.good_to_bad * self.bad_loss) / transitions
+    }
+}
+
+#[typetag::serde]
+impl Loss for GilbertElliottLoss {
+    fn is_lost(&mut self, rng: Rc<RefCell<XorShiftRng>>, sender: ProcessId, target: ProcessId) -> bool {
+        let mut rng = rng.borrow_mut();
+        let link = (sender, target);
+        let bad = if self.bad_links.contains(&link) {
+            if self.uniform.sample(&mut *rng) < self.bad_to_good {
+                self.bad_links.remove(&link);
+                false
+            } else {
+                true
+            }
+        } else if self.uniform.sample(&mut *rng) < self.good_to_bad {
+            self.bad_links.insert(link);
+            true
+        } else {
+            false
+        };
+
+        let loss = if bad { self.bad_loss } else { self.good_loss };
+        self.uniform.sample(&mut *rng) < loss
+    }
+}
+
+/// loss probability of the messages from sender to target, and also from target to sender unless the loss is directed
+#[derive(Serialize, Deserialize)]
+#[derive(Debug, Clone)]
+pub struct LinkLoss {
+    pub sender: ProcessId,
+    pub target: ProcessId,
+    pub loss: f64
+}
+
+/// Bernoulli loss with a probability for each link, where links that are not given have the default probability.
+#[derive(Serialize, Deserialize)]
+#[derive(Debug, Clone)]
+pub struct PerLinkLoss {
+    #[serde(default)]
+    default: f64,
+    links: Vec<LinkLoss>,
+    #[serde(default)]
+    directed: bool,
+    /// loss of each link, built from links on the first message
+    #[serde(skip)]
+    link_loss: HashMap<(ProcessId, ProcessId), f64>,
+    #[serde(skip, default = "uniform_default")]
+    uniform: Uniform<f64>
+}
+
+impl PerLinkLoss {
+    pub fn new(default: f64, links: Vec<LinkLoss>, directed: bool) -> Self {
+        PerLinkLoss { default, links, directed, link_loss: HashMap::new(), uniform: Uniform::new(0.0, 1.0) }
+    }
+    pub fn get_loss(&mut self, sender: ProcessId, target: ProcessId) -> f64 {
+        if self.link_loss.is_empty() {
+            // in undirected mode the later entry wins when a link is given in both directions
+            for link in &self.links {
+                if !self.directed {
+                    self.link_loss.insert((link.target, link.sender), link.loss);
+                }
+                self.link_loss.insert((link.sender, link.target), link.loss);
+            }
+        }
+        self.link_loss.get(&(sender, target)).cloned().unwrap_or(self.default)
+    }
+}
+
+#[typetag::serde]
+impl Loss for PerLinkLoss {
+    fn is_lost(&mut self, rng: Rc<RefCell<XorShiftRng>>, sender: ProcessId, target: ProcessId) -> bool {
+        let loss = self.get_loss(sender, target);
+        self.uniform.sample(&mut *rng.borrow_mut()) < loss
+    }
+}
+
+/// Applies a loss model on top of any network: a message is lost if either the loss model
+/// or the inner network lose it, otherwise its latency is given by the inner network.
+#[derive(Serialize, Deserialize)]
+#[derive(Debug, Clone)]
+pub struct LossyNetwork {
+    network: Box<dyn Network>,
+    loss: Box<dyn Loss>
+}
+
+impl LossyNetwork {
+    pub fn new(network: Box<dyn Network>, loss: Box<dyn Loss>) -> Self {
+        LossyNetwork { network, loss }
+    }
+}
+
+#[typetag::serde]
+impl Network for LossyNetwork {
+    fn get_latency(&mut self, rng: Rc<RefCell<XorShiftRng>>, sender: ProcessId, target: ProcessId) -> Option<Time> {
+        if self.loss.is_lost(rng.clone(), sender, target) {
+            None
+        } else {
+            self.network.get_latency(rng, sender, target)
+        }
+    }
+    fn set_time_unit(&mut self, unit: TimeUnit) {
+        self.network.set_time_unit(unit);
+    }
+    fn get_deliveries(&mut self, rng: Rc<RefCell<XorShiftRng>>, sender: ProcessId, target: ProcessId) -> Vec<Delivery> {
+        if self.loss.is_lost(rng.clone(), sender, target) {
+            Vec::new()
+        } else {
+            self.network.get_deliveries(rng, sender, target)
+        }
+    }
+}
+
+/// Adds faults to the messages delivered by any network, each with its own probability:
+/// - duplicate: the message is delivered twice, and the copy has its own latency given by the inner network
+/// - reorder: the message is delayed by reorder_delay more, so that messages sent after it may arrive first
+/// - corrupt: the message is delivered damaged, see Operation::corrupt and Process::is_corrupted
+#[derive(Serialize, Deserialize)]
+#[derive(Debug, Clone)]
+pub struct FaultyNetwork {
+    network: Box<dyn Network>,
+    #[serde(default)]
+    duplicate: f64,
+    #[serde(default)]
+    reorder: f64,
+    #[serde(default)]
+    reorder_delay: Time,
+    #[serde(default)]
+    corrupt: f64,
+    #[serde(skip, default = "uniform_default")]
+    uniform: Uniform<f64>
+}
+
+impl FaultyNetwork {
+    pub fn new(network: Box<dyn Network>, duplicate: f64, reorder: f64, reorder_delay: Time, corrupt: f64) -> Self {
+        FaultyNetwork { network, duplicate, reorder, reorder_delay, corrupt, uniform: Uniform::new(0.0, 1.0) }
+    }
+    fn happens(&self, rng: &Rc<RefCell<XorShiftRng>>, probability: f64) -> bool {
+        probability > 0.0 && self.uniform.sample(&mut *rng.borrow_mut()) < probability
+    }
+}
+
+#[typetag::serde]
+impl Network for FaultyNetwork {
+    fn get_latency(&mut self, rng: Rc<RefCell<XorShiftRng>>, sender: ProcessId, target: ProcessId) -> Option<Time> {
+        self.network.get_latency(rng, sender, target)
+    }
+    fn set_time_unit(&mut self, unit: TimeUnit) {
+        self.network.set_time_unit(unit);
+    }
+    fn get_deliveries(&mut self, rng: Rc<RefCell<XorShiftRng>>, sender: ProcessId, target: ProcessId) -> Vec<Delivery> {
+        let mut deliveries = self.network.get_deliveries(rng.clone(), sender, target);
+        if self.happens(&rng, self.duplicate) {
+            let copies = self.network.get_deliveries(rng.clone(), sender, target);
+            deliveries.extend(copies);
+        }
+        for delivery in &mut deliveries {
+            if self.happens(&rng, self.reorder) {
+                delivery.latency += self.reorder_delay;
+            }
+            if self.happens(&rng, self.corrupt) {
+                delivery.corrupted = true;
+            }
+        }
+        deliveries
+    }
+}
+
+/// Distribution from which each coordinate, or the height, of a process is drawn
+#[derive(Serialize, Deserialize)]
+#[derive(Debug, Clone)]
+#[serde(rename_all = "lowercase")]
+pub enum CoordinateDistribution {
+    Uniform(UniformWrapper),
+    Normal(NormalWrapper)
+}
+
+impl CoordinateDistribution {
+    pub fn uniform(low: f64, high: f64) -> Self {
+        CoordinateDistribution::Uniform(UniformWrapper::new(low, high))
+    }
+    pub fn normal(mean: f64, std_dev: f64) -> Self {
+        CoordinateDistribution::Normal(NormalWrapper::new(mean, std_dev))
+    }
+    fn sample(&self, rng: &mut XorShiftRng) -> f64 {
+        match self {
+            CoordinateDistribution::Uniform(uniform) => uniform.sample(rng),
+            CoordinateDistribution::Normal(normal) => normal.sample(rng)
+        }
+    }
+}
+
+/// maximum number of dimensions of a coordinate, without the height
+const MAX_DIMENSIONS: usize = 3;
+
+/// Network where each process has a coordinate in a 1, 2 or 3 dimensional euclidean space plus a height, as in Vivaldi,
+/// and the latency between two processes is scale * (distance between their coordinates + their heights),
+/// so latencies respect the triangle inequality.
+///
+/// The coordinates of a process are either drawn from the distributions with a generator seeded with seed and the process id,
+/// so they are the same in every run and need no memory, or read from a file.
+#[derive(Serialize)]
+#[derive(Debug, Clone)]
+pub struct CoordinateNetwork {
+    dimensions: u8,
+    coordinates: Option<CoordinateDistribution>,
+    height: Option<CoordinateDistribution>,
+    seed: u64,
+    /// dimensions coordinates followed by the height of each process read from a file
+    points: Vec<f64>,
+    scale: f64,
+    jitter: Box<dyn Jitter>,
+    loss: f64,
+    #[serde(skip, default = "uniform_default")]
+    uniform: Uniform<f64>
+}
+
+impl CoordinateNetwork {
+    /// the coordinates are drawn from coordinates and the height, when given, from height
+    pub fn new(dimensions: u8, coordinates: CoordinateDistribution, height: Option<CoordinateDistribution>, scale: f64, seed: u64, jitter: Box<dyn Jitter>, loss: f64) -> Result<Self, Error> {
+        CoordinateNetwork::check_dimensions(dimensions).map_err(Error::Simulation)?;
+        Ok(CoordinateNetwork { dimensions, coordinates: Some(coordinates), height, seed, points: Vec::new(), scale, jitter, loss, uniform: Uniform::new(0.0, 1.0) })
+    }
+    /// assumes file with the following structure:
+    /// process id, then dimensions coordinates and optionally the height, each separated by '\t',
+    /// where the processes go from 0 to num_processes-1 and process id > num_processes-1 has the coordinate of id % num_processes
+    pub fn new_from_file<P: AsRef<Path>>(filename: P, dimensions: u8, scale: f64, jitter: Box<dyn Jitter>, loss: f64) -> Result<Self, Error> {
+        let file_display = filename.as_ref().display().to_string();
+        CoordinateNetwork::check_dimensions(dimensions).map_err(|msg| Error::config(file_display.clone(), None, msg))?;
+
+        let stride = dimensions as usize + 1;
+        let mut points = Vec::new();
+        let mut num_processes = 0;
+        let file = File::open(&filename).map_err(|source| Error::Io { file: file_display.clone(), source })?;
+        for (line_index, line_result) in BufReader::new(file).lines().enumerate() {
+            let line = line_result.map_err(|source| Error::Io { file: file_display.clone(), source })?;
+            let error = |msg: String| Error::config(file_display.clone(), Some(line_index + 1), msg);
+            if line.trim().is_empty() {
+                continue;
+            }
+            let content = line.split('\t').collect::<Vec<&str>>();
+            if content.len() != stride && content.len() != stride + 1 {
+                return Err(error(format!("expected process id, {} coordinates and optionally the height separated by '\\t'", dimensions)));
+            }
+
+            let id = content[0].trim().parse::<ProcessId>().map_err(|_| error("process must be a non-negative integer".to_string()))? as usize;
+            if (id + 1) * stride > points.len() {
+                points.resize((id + 1) * stride, 0.0);
+                num_processes = id + 1;
+            }
+            for (k, value) in content[1..].iter().enumerate() {
+                let value = value.trim().parse::<f64>().map_err(|_| error("coordinates and height must be numbers".to_string()))?;
+                points[id * stride + k] = value;
+            }
+            if points[id * stride + dimensions as usize] < 0.0 {
+                return Err(error("height must not be negative".to_string()));
+            }
+        }
+        if num_processes == 0 {
+            return Err(Error::config(file_display, None, "the coordinates file must have at least one process"));
+        }
+
+        Ok(CoordinateNetwork { dimensions, coordinates: None, height: None, seed: 0, points, scale, jitter, loss, uniform: Uniform::new(0.0, 1.0) })
+    }
+    fn check_dimensions(dimensions: u8) -> Result<(), String> {
+        if dimensions == 0 || dimensions as usize > MAX_DIMENSIONS {
+            return Err(format!("CoordinateNetwork supports from 1 to {} dimensions, but received {}", MAX_DIMENSIONS, dimensions));
+        }
+        Ok(())
+    }
+    /// returns the coordinates of process id followed by its height
+    fn get_point(&self, id: ProcessId) -> [f64; MAX_DIMENSIONS + 1] {
+        let dimensions = self.dimensions as usize;
+        let mut point = [0.0; MAX_DIMENSIONS + 1];
+        match &self.coordinates {
+            Some(coordinates) => {
+                let mut rng = XorShiftRng::seed_from_u64(utils::mix64(self.seed ^ utils::mix64(id as u64)));
+                for coordinate in point.iter_mut().take(dimensions) {
+                    *coordinate = coordinates.sample(&mut rng);
+                }
+                if let Some(height) = &self.height {
+                    point[dimensions] = height.sample(&mut rng).abs();
+                }
+            },
+            None => {
+                let stride = dimensions + 1;
+                let num_processes = self.points.len() / stride;
+                let i = id as usize % num_processes;
+                point[..stride].copy_from_slice(&self.points[i * stride..(i + 1) * stride]);
+            }
+        }
+        point
+    }
+    /// latency between sender and target, without jitter
+    pub fn get_distance_latency(&self, sender: ProcessId, target: ProcessId) -> Time {
+        if sender == target {
+            return 0;
+        }
+        let dimensions = self.dimensions as usize;
+        let a = self.get_point(sender);
+        let b = self.get_point(target);
+        let distance = a.iter().zip(b.iter()).take(dimensions).map(|(x, y)| (x - y) * (x - y)).sum::<f64>().sqrt();
+        (self.scale * (distance + a[dimensions] + b[dimensions])).round() as Time
+    }
+}
+
+#[typetag::serde]
+impl Network for CoordinateNetwork {
+    fn get_latency(&mut self, rng: Rc<RefCell<XorShiftRng>>, sender: ProcessId, target: ProcessId) -> Option<Time> {
+        if self.uniform.sample(&mut *rng.borrow_mut()) < self.loss {
+            None
+        } else {
+            let latency = self.get_distance_latency(sender, target);
+            Some(latency + self.jitter.get_jitter(rng.clone(), latency))
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for CoordinateNetwork {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: Deserializer<'de>,
+    {
+        /// either the configuration, with coordinates or coordinates_file, or a snapshot, with coordinates or points
+        #[derive(Deserialize)]
+        struct CoordinateNetworkFields {
+            #[serde(default = "default_dimensions")]
+            dimensions: u8,
+            coordinates: Option<CoordinateDistribution>,
+            height: Option<CoordinateDistribution>,
+            #[serde(default)]
+            seed: u64,
+            coordinates_file: Option<String>,
+            #[serde(default)]
+            points: Vec<f64>,
+            #[serde(default = "default_scale")]
+            scale: f64,
+            jitter: Box<dyn Jitter>,
+            loss: f64
+        }
+        fn default_dimensions() -> u8 { 2 }
+        fn default_scale() -> f64 { 1.0 }
+
+        let f = CoordinateNetworkFields::deserialize(deserializer)?;
+        match (f.coordinates, f.coordinates_file) {
+            (Some(coordinates), None) => CoordinateNetwork::new(f.dimensions, coordinates, f.height, f.scale, f.seed, f.jitter, f.loss).map_err(de::Error::custom),
+            (None, Some(coordinates_file)) => CoordinateNetwork::new_from_file(coordinates_file, f.dimensions, f.scale, f.jitter, f.loss).map_err(de::Error::custom),
+            (None, None) if !f.points.is_empty() => {
+                CoordinateNetwork::check_dimensions(f.dimensions).map_err(de::Error::custom)?;
+                Ok(CoordinateNetwork { dimensions: f.dimensions, coordinates: None, height: None, seed: 0, points: f.points, scale: f.scale, jitter: f.jitter, loss: f.loss, uniform: Uniform::new(0.0, 1.0) })
+            },
+            _ => Err(de::Error::custom("CoordinateNetwork expects either coordinates or coordinates_file"))
+        }
+    }
+}
+
 impl Default for Box<Network> {
     fn default() -> Self {
         Box::new(ConstantNetwork::default())
@@ -355,30 +1013,42 @@ impl Default for Box<Network> {
 
 #[cfg(test)]
 mod test {
-    use crate::simulation::Time;
+    use std::rc::Rc;
+    use std::cell::RefCell;
+    use rand::SeedableRng;
+    use rand_xorshift::XorShiftRng;
+
+    use crate::simulation::{Time, TimeUnit};
+    use crate::simulation::network::Network;
     use crate::simulation::network::NoJitter;
     use crate::simulation::network::MatrixNetwork;
+    use crate::simulation::network::SiteNetwork;
+    use crate::simulation::domains::{DomainsConf, FailureDomains};
+    use crate::simulation::network::{CoordinateNetwork, CoordinateDistribution};
+    use crate::simulation::network::{ConstantNetwork, LossyNetwork, BernoulliLoss, GilbertElliottLoss, PerLinkLoss, LinkLoss};
+    use crate::simulation::network::{FaultyNetwork, Delivery};
+    use crate::simulation::ProcessId;
 
     static FILENAME: &str = "data/pl_226nodes.latencies";
 
     #[test]
     fn test_matrix_exact_nodes() {
         let total_nodes = 226;
-        let network = MatrixNetwork::new(FILENAME, total_nodes, Box::new(NoJitter), 0.0);
+        let network = MatrixNetwork::new(FILENAME, total_nodes, Box::new(NoJitter), 0.0).unwrap();
         assert_eq!(network.latency_matrix.len(), 226);
     }
 
     #[test]
     fn test_matrix_less_nodes() {
         let total_nodes = 10;
-        let network = MatrixNetwork::new(FILENAME, total_nodes, Box::new(NoJitter), 0.0);
+        let network = MatrixNetwork::new(FILENAME, total_nodes, Box::new(NoJitter), 0.0).unwrap();
         assert_eq!(network.latency_matrix.len(), 10);
     }
 
     #[test]
     fn test_matrix_one_more_node() {
         let total_nodes = 227;
-        let network = MatrixNetwork::new(FILENAME, total_nodes, Box::new(NoJitter), 0.0);
+        let network = MatrixNetwork::new(FILENAME, total_nodes, Box::new(NoJitter), 0.0).unwrap();
         assert_eq!(network.latency_matrix.len(), total_nodes as usize);
         for i in 1..(total_nodes-1) {
             let latency = network.latency_matrix[(total_nodes-1) as usize][i as usize];
@@ -394,12 +1064,182 @@ mod test {
         assert_eq!(network.latency_matrix[(total_nodes-1) as usize][0], latency.round() as Time);
         assert_eq!(network.latency_matrix[(total_nodes-1) as usize][(total_nodes-1) as usize], 0);
     }
+
+    #[test]
+    fn test_matrix_latency_unit() {
+        let total_nodes = 227;
+        let network = MatrixNetwork::new(FILENAME, total_nodes, Box::new(NoJitter), 0.0).unwrap();
+
+        let mut network_ms = MatrixNetwork::new_with_options(FILENAME, total_nodes, Some(TimeUnit::Ms), false, Box::new(NoJitter), 0.0).unwrap();
+        network_ms.set_time_unit(TimeUnit::Ms);
+        assert_eq!(network_ms.latency_matrix, network.latency_matrix);
+
+        let mut network_us = MatrixNetwork::new_with_options(FILENAME, total_nodes, Some(TimeUnit::Ms), false, Box::new(NoJitter), 0.0).unwrap();
+        network_us.set_time_unit(TimeUnit::Us);
+        // 70 4 380.835968 in the file, which overwrites 4 70 382.249984
+        assert_eq!(network_us.latency_matrix[70][4], 380836);
+        for i in 0..total_nodes as usize {
+            for j in 0..i {
+                assert!((network_us.latency_matrix[i][j] - network.latency_matrix[i][j] * 1000).abs() <= 500);
+            }
+        }
+    }
+
+    #[test]
+    fn test_matrix_directed() {
+        let total_nodes = 228;
+        let mut network = MatrixNetwork::new_with_options(FILENAME, total_nodes, None, true, Box::new(NoJitter), 0.0).unwrap();
+        let symmetric = MatrixNetwork::new(FILENAME, total_nodes, Box::new(NoJitter), 0.0).unwrap();
+        let rng = Rc::new(RefCell::new(XorShiftRng::seed_from_u64(0)));
+
+        // 4 70 382.249984 and 70 4 380.835968 in the file
+        assert_eq!(network.get_latency(rng.clone(), 4, 70), Some(382));
+        assert_eq!(network.get_latency(rng.clone(), 70, 4), Some(381));
+        assert_eq!(MatrixNetwork::get_latency(&symmetric.latency_matrix, false, 4, 70), 381);
+
+        // the nodes that are not in the file copy the latencies of node i % 226
+        for j in 1..226 {
+            assert_eq!(network.get_latency(rng.clone(), 226, j), network.get_latency(rng.clone(), 0, j));
+            assert_eq!(network.get_latency(rng.clone(), j, 226), network.get_latency(rng.clone(), j, 0));
+            if j != 1 {
+                assert_eq!(network.get_latency(rng.clone(), 227, j), network.get_latency(rng.clone(), 1, j));
+            }
+        }
+        assert_eq!(network.get_latency(rng.clone(), 227, 227), Some(0));
+        assert_ne!(network.get_latency(rng.clone(), 226, 0), Some(0));
+        assert_ne!(network.get_latency(rng.clone(), 0, 226), Some(0));
+    }
+
+    #[test]
+    fn test_site_network() {
+        let sites = MatrixNetwork::new(FILENAME, 10, Box::new(NoJitter), 0.0).unwrap();
+        let rng = Rc::new(RefCell::new(XorShiftRng::seed_from_u64(0)));
+
+        let mut network = SiteNetwork::new(sites.clone(), 1);
+        assert_eq!(network.get_latency(rng.clone(), 999_999, 9), Some(1));
+        assert_eq!(network.get_latency(rng.clone(), 1_000_003, 7), Some(MatrixNetwork::get_latency(&sites.latency_matrix, false, 3, 7)));
+
+        let mut network = SiteNetwork::new_from_file(sites.clone(), "config/test/process-sites.txt", 1).unwrap();
+        assert_eq!((network.get_site(0), network.get_site(2), network.get_site(3), network.get_site(4), network.get_site(15)), (3, 5, 3, 1, 5));
+        assert_eq!(network.get_latency(rng.clone(), 0, 1), Some(1));
+        assert_eq!(network.get_latency(rng.clone(), 0, 3), Some(1));
+        assert_eq!(network.get_latency(rng.clone(), 2, 4), Some(MatrixNetwork::get_latency(&sites.latency_matrix, false, 5, 1)));
+
+        assert!(SiteNetwork::new_from_file(MatrixNetwork::new(FILENAME, 4, Box::new(NoJitter), 0.0).unwrap(), "config/test/process-sites.txt", 1).is_err());
+
+        let domains = FailureDomains::new(&DomainsConf { count: 10, file: Some("config/test/process-sites.txt".to_string()) }).unwrap();
+        let network = SiteNetwork::new_from_domains(sites.clone(), &domains, 1).unwrap();
+        assert_eq!((network.get_site(0), network.get_site(2), network.get_site(4), network.get_site(15)), (3, 5, 1, 5));
+        let domains = FailureDomains::new(&DomainsConf { count: 6, file: None }).unwrap();
+        assert!(SiteNetwork::new_from_domains(sites.clone(), &domains, 1).is_err());
+    }
+
+    #[test]
+    fn test_coordinate_network() {
+        let network = CoordinateNetwork::new(3, CoordinateDistribution::uniform(0.0, 100.0), Some(CoordinateDistribution::uniform(0.0, 5.0)), 1.0, 7, Box::new(NoJitter), 0.0).unwrap();
+        let same_seed = CoordinateNetwork::new(3, CoordinateDistribution::uniform(0.0, 100.0), Some(CoordinateDistribution::uniform(0.0, 5.0)), 1.0, 7, Box::new(NoJitter), 0.0).unwrap();
+        let other_seed = CoordinateNetwork::new(3, CoordinateDistribution::uniform(0.0, 100.0), Some(CoordinateDistribution::uniform(0.0, 5.0)), 1.0, 8, Box::new(NoJitter), 0.0).unwrap();
+
+        let ids: Vec<ProcessId> = vec![0, 1, 2, 17, 999, 123_456, 4_000_000_000];
+        for &a in &ids {
+            assert_eq!(network.get_distance_latency(a, a), 0);
+            for &b in &ids {
+                assert_eq!(network.get_distance_latency(a, b), network.get_distance_latency(b, a));
+                assert_eq!(network.get_distance_latency(a, b), same_seed.get_distance_latency(a, b));
+                for &c in &ids {
+                    // up to 1 for each rounding
+                    assert!(network.get_distance_latency(a, c) <= network.get_distance_latency(a, b) + network.get_distance_latency(b, c) + 2);
+                }
+            }
+        }
+        assert_ne!(network.get_distance_latency(0, 1), other_seed.get_distance_latency(0, 1));
+
+        assert!(CoordinateNetwork::new(4, CoordinateDistribution::normal(0.0, 1.0), None, 1.0, 0, Box::new(NoJitter), 0.0).is_err());
+
+        let network = CoordinateNetwork::new_from_file("config/test/coordinates.txt", 2, 10.0, Box::new(NoJitter), 0.0).unwrap();
+        assert_eq!(network.get_distance_latency(0, 1), 60);
+        assert_eq!(network.get_distance_latency(0, 2), 100);
+        assert_eq!(network.get_distance_latency(0, 4), 60);
+        assert_eq!(network.get_distance_latency(1, 4), 20);
+        assert!(CoordinateNetwork::new_from_file("config/test/coordinates.txt", 3, 10.0, Box::new(NoJitter), 0.0).is_err());
+    }
+
+    /// returns the fraction of lost messages and the mean length of the bursts of lost messages
+    fn loss_bursts(network: &mut LossyNetwork, sender: ProcessId, target: ProcessId, messages: u32) -> (f64, f64) {
+        let rng = Rc::new(RefCell::new(XorShiftRng::seed_from_u64(0)));
+        let (mut lost, mut bursts, mut previous_lost) = (0, 0, false);
+        for _ in 0..messages {
+            let is_lost = network.get_latency(rng.clone(), sender, target).is_none();
+            if is_lost {
+                lost += 1;
+                if !previous_lost {
+                    bursts += 1;
+                }
+            }
+            previous_lost = is_lost;
+        }
+        (lost as f64 / messages as f64, lost as f64 / bursts as f64)
+    }
+
+    #[test]
+    fn test_loss_models() {
+        let constant = || Box::new(ConstantNetwork::new(100, Box::new(NoJitter), 0.0));
+
+        let mut bernoulli = LossyNetwork::new(constant(), Box::new(BernoulliLoss::new(0.1)));
+        let (loss, burst) = loss_bursts(&mut bernoulli, 0, 1, 100_000);
+        assert!((loss - 0.1).abs() < 0.01);
+        assert!(burst < 1.2);
+
+        // same mean loss, but with bursts of 1 / bad_to_good = 10 messages
+        let gilbert_elliott = GilbertElliottLoss::new(1.0 / 90.0, 0.1, 0.0, 1.0);
+        assert!((gilbert_elliott.mean_loss() - 0.1).abs() < 1e-9);
+        let mut network = LossyNetwork::new(constant(), Box::new(gilbert_elliott));
+        let (loss, burst) = loss_bursts(&mut network, 0, 1, 100_000);
+        assert!((loss - 0.1).abs() < 0.02);
+        assert!(burst > 8.0 && burst < 12.0);
+
+        let links = vec![LinkLoss { sender: 0, target: 1, loss: 1.0 }, LinkLoss { sender: 2, target: 3, loss: 0.5 }];
+        let mut network = LossyNetwork::new(constant(), Box::new(PerLinkLoss::new(0.0, links.clone(), false)));
+        assert_eq!(loss_bursts(&mut network, 0, 1, 1000).0, 1.0);
+        assert_eq!(loss_bursts(&mut network, 1, 0, 1000).0, 1.0);
+        assert_eq!(loss_bursts(&mut network, 0, 2, 1000).0, 0.0);
+        assert!((loss_bursts(&mut network, 3, 2, 10_000).0 - 0.5).abs() < 0.05);
+
+        let mut directed = LossyNetwork::new(constant(), Box::new(PerLinkLoss::new(0.2, links, true)));
+        assert_eq!(loss_bursts(&mut directed, 0, 1, 1000).0, 1.0);
+        assert!((loss_bursts(&mut directed, 1, 0, 10_000).0 - 0.2).abs() < 0.05);
+    }
+
+
+    #[test]
+    fn test_faulty_network() {
+        let rng = Rc::new(RefCell::new(XorShiftRng::seed_from_u64(0)));
+        let constant = || Box::new(ConstantNetwork::new(100, Box::new(NoJitter), 0.0));
+
+        let mut network = FaultyNetwork::new(constant(), 0.0, 0.0, 0, 0.0);
+        assert_eq!(network.get_deliveries(rng.clone(), 0, 1), vec![Delivery::new(100)]);
+
+        let mut network = FaultyNetwork::new(constant(), 1.0, 1.0, 50, 1.0);
+        assert_eq!(network.get_deliveries(rng.clone(), 0, 1), vec![Delivery { latency: 150, corrupted: true }; 2]);
+
+        let mut network = FaultyNetwork::new(constant(), 0.2, 0.0, 0, 0.0);
+        let copies: usize = (0..10_000).map(|_| network.get_deliveries(rng.clone(), 0, 1).len()).sum();
+        assert!((copies as f64 / 10_000.0 - 1.2).abs() < 0.02);
+
+        // the copy of a duplicated message may be lost on its own
+        let lossy = Box::new(LossyNetwork::new(constant(), Box::new(BernoulliLoss::new(0.5))));
+        let mut network = FaultyNetwork::new(lossy, 1.0, 0.0, 0, 0.0);
+        let counts: Vec<usize> = (0..1000).map(|_| network.get_deliveries(rng.clone(), 0, 1).len()).collect();
+        assert!((0..3).all(|c| counts.contains(&c)));
+    }
+
 }
 
 
 #[cfg(test)]
 mod test_checkpointing {
     use super::*;
+    use rand::SeedableRng;
 
     use serde_yaml;
 
@@ -441,6 +1281,65 @@ mod test_checkpointing {
         let _deserialized: ConfNetwork = serde_yaml::from_str(&s).unwrap();
     }
 
+    #[test]
+    fn serde_site_network() {
+        let s: &str = "network:\n  type: SiteNetwork\n  sites:\n    latency_file: \"data/pl_226nodes.latencies\"\n    n: 10\n    jitter:\n      type: NoJitter\n    loss: 0.0\n  sites_file: config/test/process-sites.txt\n  local_latency: 2";
+        let deserialized: ConfNetwork = serde_yaml::from_str(s).unwrap();
+
+        let serialized = bincode::serialize(&deserialized.network).unwrap();
+        let mut network: Box<dyn Network> = bincode::deserialize(&serialized).unwrap();
+        let rng = Rc::new(RefCell::new(XorShiftRng::seed_from_u64(0)));
+        assert_eq!(network.get_latency(rng.clone(), 0, 3), Some(2));
+        assert_eq!(network.get_latency(rng.clone(), 2, 4), Some(MatrixNetwork::get_latency(&MatrixNetwork::new("data/pl_226nodes.latencies", 10, Box::new(NoJitter), 0.0).unwrap().latency_matrix, false, 5, 1)));
+    }
+
+    #[test]
+    fn serde_coordinate_network() {
+        let s: &str = "network:\n  type: CoordinateNetwork\n  dimensions: 2\n  coordinates:\n    normal:\n      mean: 50.0\n      std_dev: 20.0\n  height:\n    uniform:\n      low: 0.0\n      high: 2.0\n  seed: 3\n  jitter:\n    type: NoJitter\n  loss: 0.0";
+        let mut deserialized: ConfNetwork = serde_yaml::from_str(s).unwrap();
+        let rng = Rc::new(RefCell::new(XorShiftRng::seed_from_u64(0)));
+        let latency = deserialized.network.get_latency(rng.clone(), 1, 2);
+
+        let serialized = bincode::serialize(&deserialized.network).unwrap();
+        let mut network: Box<dyn Network> = bincode::deserialize(&serialized).unwrap();
+        assert_eq!(network.get_latency(rng.clone(), 1, 2), latency);
+
+        let s: &str = "network:\n  type: CoordinateNetwork\n  coordinates_file: config/test/coordinates.txt\n  scale: 10.0\n  jitter:\n    type: NoJitter\n  loss: 0.0";
+        let deserialized: ConfNetwork = serde_yaml::from_str(s).unwrap();
+        let serialized = bincode::serialize(&deserialized.network).unwrap();
+        let mut network: Box<dyn Network> = bincode::deserialize(&serialized).unwrap();
+        assert_eq!(network.get_latency(rng.clone(), 0, 4), Some(60));
+    }
+
+    #[test]
+    fn serde_lossy_network() {
+        let s: &str = "network:\n  type: LossyNetwork\n  network:\n    type: ConstantNetwork\n    latency: 50\n    jitter:\n      type: NoJitter\n    loss: 0.0\n  loss:\n    type: GilbertElliottLoss\n    good_to_bad: 0.05\n    bad_to_good: 0.3";
+        let mut deserialized: ConfNetwork = serde_yaml::from_str(s).unwrap();
+        let rng = Rc::new(RefCell::new(XorShiftRng::seed_from_u64(0)));
+        for _ in 0..20 {
+            deserialized.network.get_latency(rng.clone(), 0, 1);
+        }
+
+        // the state of the links is kept in the snapshot
+        let serialized = bincode::serialize(&deserialized.network).unwrap();
+        let mut network: Box<dyn Network> = bincode::deserialize(&serialized).unwrap();
+        let rng2 = Rc::new(RefCell::new(rng.borrow().clone()));
+        for _ in 0..100 {
+            assert_eq!(network.get_latency(rng2.clone(), 0, 1), deserialized.network.get_latency(rng.clone(), 0, 1));
+        }
+
+        let s: &str = "network:\n  type: LossyNetwork\n  network:\n    type: ConstantNetwork\n    latency: 50\n    jitter:\n      type: NoJitter\n    loss: 0.0\n  loss:\n    type: PerLinkLoss\n    links:\n      - {sender: 1, target: 2, loss: 1.0}";
+        let mut deserialized: ConfNetwork = serde_yaml::from_str(s).unwrap();
+        assert_eq!(deserialized.network.get_latency(rng.clone(), 2, 1), None);
+        assert_eq!(deserialized.network.get_latency(rng.clone(), 0, 1), Some(50));
+
+        let s: &str = "network:\n  type: FaultyNetwork\n  network:\n    type: ConstantNetwork\n    latency: 50\n    jitter:\n      type: NoJitter\n    loss: 0.0\n  duplicate: 1.0\n  corrupt: 1.0";
+        let deserialized: ConfNetwork = serde_yaml::from_str(s).unwrap();
+        let serialized = bincode::serialize(&deserialized.network).unwrap();
+        let mut network: Box<dyn Network> = bincode::deserialize(&serialized).unwrap();
+        assert_eq!(network.get_deliveries(rng.clone(), 0, 1), vec![Delivery { latency: 50, corrupted: true }; 2]);
+    }
+
     #[test]
     fn serde_network() {
         let jitter = Box::new(LogNormalJitter::new(0.0, 0.1));
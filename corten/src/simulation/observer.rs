@@ -0,0 +1,151 @@
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0>.
+// This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::rc::Rc;
+use std::cell::RefCell;
+use std::fmt::{self, Debug};
+
+use crate::simulation::{Time, ProcessId, Operation};
+
+/// Receives a callback for everything the kernel does, for e.g. to collect metrics, trace or check invariants.
+/// All the methods do nothing by default, so an observer only implements the ones it is interested in.
+///
+/// Observers are registered with SimulationKernel::add_observer and are not saved in snapshots,
+/// so they have to be registered again after loading.
+pub trait SimulationObserver {
+    /// a process sent a message that will be delivered to process to at delivery_ts
+    fn on_message_sent(&mut self, _ts: Time, _from: ProcessId, _to: ProcessId, _delivery_ts: Time, _op: &dyn Operation) {}
+    /// a message was lost by the network, or its target was down when it arrived
+    fn on_message_dropped(&mut self, _ts: Time, _from: ProcessId, _to: ProcessId, _op: &dyn Operation) {}
+    /// the kernel executed an event, kind is local, message, save or the churn action (for e.g. join, fail, end)
+    /// target is the process that received the event, and 0 for churn and save events
+    fn on_event_executed(&mut self, _ts: Time, _target: ProcessId, _kind: &str) {}
+    /// the processes ids were affected by a churn action, for e.g. join, leave, fail or recover
+    fn on_churn(&mut self, _ts: Time, _kind: &str, _ids: &[ProcessId]) {}
+    /// a snapshot of the simulation was saved in filename
+    fn on_save(&mut self, _ts: Time, _filename: &str) {}
+    /// the failure detector of process id started to suspect that suspected failed
+    fn on_suspect(&mut self, _ts: Time, _id: ProcessId, _suspected: ProcessId) {}
+    /// the failure detector of process id stopped suspecting restored
+    fn on_restore(&mut self, _ts: Time, _id: ProcessId, _restored: ProcessId) {}
+}
+
+impl Debug for dyn SimulationObserver {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "SimulationObserver")
+    }
+}
+
+/// observers registered in a kernel, shared with all its processes
+pub type Observers = Rc<RefCell<Vec<Rc<RefCell<dyn SimulationObserver>>>>>;
+
+pub fn default_observers() -> Observers {
+    Rc::new(RefCell::new(Vec::new()))
+}
+
+/// calls f for each registered observer, by the order in which they were registered
+pub fn notify<F: Fn(&mut dyn SimulationObserver)>(observers: &Observers, f: F) {
+    for observer in &*observers.borrow() {
+        f(&mut *observer.borrow_mut());
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::rc::Rc;
+    use std::cell::RefCell;
+    use std::fs;
+
+    use crate::simulation::{SimulationBuilder, Operation, ProcessId, Time};
+    use crate::simulation::asynchrony::NoAsynchrony;
+    use crate::simulation::network::{ConstantNetwork, NoJitter};
+    use crate::simulation::observer::SimulationObserver;
+    use crate::simulation::test::{echo_apps, snapshot_path};
+
+    use crate::echo_lib::AppConf;
+
+    #[derive(Default)]
+    struct Counter {
+        sent: u32,
+        dropped: u32,
+        messages_executed: u32,
+        events_executed: u64,
+        joined: Vec<ProcessId>,
+        saves: Vec<(Time, String)>
+    }
+
+    impl SimulationObserver for Counter {
+        fn on_message_sent(&mut self, ts: Time, _from: ProcessId, _to: ProcessId, delivery_ts: Time, _op: &dyn Operation) {
+            assert!(delivery_ts >= ts);
+            self.sent += 1;
+        }
+        fn on_message_dropped(&mut self, _ts: Time, _from: ProcessId, _to: ProcessId, _op: &dyn Operation) {
+            self.dropped += 1;
+        }
+        fn on_event_executed(&mut self, _ts: Time, _target: ProcessId, kind: &str) {
+            if kind == "message" {
+                self.messages_executed += 1;
+            }
+            self.events_executed += 1;
+        }
+        fn on_churn(&mut self, _ts: Time, kind: &str, ids: &[ProcessId]) {
+            if kind == "join" {
+                self.joined.extend_from_slice(ids);
+            }
+        }
+        fn on_save(&mut self, ts: Time, filename: &str) {
+            self.saves.push((ts, filename.to_string()));
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "checkpointing")]
+    fn test_observer() {
+        let app_conf = Rc::new(AppConf::new(10, 3, 3, 100));
+        let apps = echo_apps(app_conf.clone());
+        let snapshot = snapshot_path("observer");
+
+        let builder = SimulationBuilder::new(10)
+            .network(Box::new(ConstantNetwork::new(10, Box::new(NoJitter), 0.5)))
+            .asynchrony(Box::new(NoAsynchrony::new()))
+            .churn("churn:\n  - [0, join, 1.0]\n  - [1000, end]")
+            .save(50)
+            .save_filename(snapshot.as_str());
+        let mut kernel = builder.build(&apps).unwrap();
+
+        let counter = Rc::new(RefCell::new(Counter::default()));
+        let counter2 = Rc::new(RefCell::new(Counter::default()));
+        kernel.add_observer(counter.clone());
+        kernel.add_observer(counter2.clone());
+        kernel.run(builder.conf()).unwrap();
+        let _ = fs::remove_file(&snapshot);
+
+        let c = counter.borrow();
+        let mut joined = c.joined.clone();
+        joined.sort();
+        assert_eq!(joined, (0..10).collect::<Vec<ProcessId>>());
+        assert!(c.sent > 0);
+        assert!(c.dropped > 0);
+        assert_eq!(c.messages_executed, c.sent);
+        assert_eq!(c.events_executed, kernel.get_events_processed());
+        assert_eq!(c.saves, vec![(50, snapshot)]);
+
+        let c2 = counter2.borrow();
+        assert_eq!((c2.sent, c2.dropped, c2.events_executed), (c.sent, c.dropped, c.events_executed));
+
+        // without losses, each message sent is either executed or dropped because its target failed
+        let builder = SimulationBuilder::new(10)
+            .network(Box::new(ConstantNetwork::new(10, Box::new(NoJitter), 0.0)))
+            .asynchrony(Box::new(NoAsynchrony::new()))
+            .churn("churn:\n  - [0, join, 1.0]\n  - [205, fail-id, [1, 2]]\n  - [1000, end]");
+        let mut kernel = builder.build(&echo_apps(app_conf)).unwrap();
+        let counter = Rc::new(RefCell::new(Counter::default()));
+        kernel.add_observer(counter.clone());
+        kernel.run(builder.conf()).unwrap();
+        let c = counter.borrow();
+        assert!(c.dropped > 0);
+        assert_eq!(c.messages_executed + c.dropped, c.sent);
+    }
+}
@@ -0,0 +1,169 @@
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0>.
+// This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::collections::BTreeMap;
+
+use serde::{Serialize, Deserialize};
+
+use crate::simulation::{Time, ProcessId};
+
+/// Changes the messages of some processes or links on top of the network, for e.g. to have processes on slow links,
+/// or a link that degrades at some point of the simulation.
+/// Overrides are given by network_overrides in the configuration, or by the override churn action,
+/// and apply from time from until time until, if given.
+/// An override with no processes and no links applies to all messages.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct NetworkOverride {
+    /// identifies the override in the override-end churn action, by default override-<index> in the configuration
+    /// and churn-<entry> in the churn, where index and entry start at 0
+    #[serde(default)]
+    pub name: Option<String>,
+    /// messages sent or received by any of these processes
+    #[serde(default)]
+    pub processes: Vec<ProcessId>,
+    /// messages between these pairs of processes, in both directions unless directed
+    #[serde(default)]
+    pub links: Vec<(ProcessId, ProcessId)>,
+    #[serde(default)]
+    pub directed: bool,
+    /// added to the latency given by the network, after multiplying it by latency_factor
+    #[serde(default)]
+    pub extra_latency: Time,
+    #[serde(default = "latency_factor_default")]
+    pub latency_factor: f64,
+    /// probability of losing a message, on top of the loss of the network
+    #[serde(default)]
+    pub loss: f64,
+    #[serde(default)]
+    pub from: Time,
+    #[serde(default)]
+    pub until: Option<Time>
+}
+
+fn latency_factor_default() -> f64 {
+    1.0
+}
+
+impl Default for NetworkOverride {
+    fn default() -> Self {
+        NetworkOverride { name: None, processes: Vec::new(), links: Vec::new(), directed: false, extra_latency: 0, latency_factor: 1.0, loss: 0.0, from: 0, until: None }
+    }
+}
+
+impl NetworkOverride {
+    /// whether the override applies to the messages from sender to target
+    pub fn matches(&self, sender: ProcessId, target: ProcessId) -> bool {
+        if self.processes.is_empty() && self.links.is_empty() {
+            return true;
+        }
+        self.processes.iter().any(|&id| id == sender || id == target)
+            || self.links.iter().any(|&(s, t)| (s == sender && t == target) || (!self.directed && s == target && t == sender))
+    }
+    /// processes affected by the override, in the order in which they are given
+    pub fn get_ids(&self) -> Vec<ProcessId> {
+        let mut ids = self.processes.clone();
+        for &(sender, target) in &self.links {
+            ids.push(sender);
+            ids.push(target);
+        }
+        ids
+    }
+}
+
+/// Combined effect of the overrides that apply to a message
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LinkEffect {
+    pub latency_factor: f64,
+    pub extra_latency: Time,
+    pub loss: f64
+}
+
+impl LinkEffect {
+    pub fn apply(&self, latency: Time) -> Time {
+        (latency as f64 * self.latency_factor).round() as Time + self.extra_latency
+    }
+}
+
+/// Overrides known to the kernel, by name, and which of them are in effect.
+/// Activating or ending an override is idempotent, so the override events may be scheduled again after a load.
+#[cfg_attr(feature = "checkpointing", derive(Serialize, Deserialize))]
+#[derive(Debug, Default)]
+pub struct NetworkOverrides {
+    declared: BTreeMap<String, NetworkOverride>,
+    /// names of the overrides in effect, by the order in which they were activated
+    active: Vec<String>
+}
+
+impl NetworkOverrides {
+    pub fn declare(&mut self, name: String, network_override: NetworkOverride) {
+        self.declared.insert(name, network_override);
+    }
+    pub fn get(&self, name: &str) -> Option<&NetworkOverride> {
+        self.declared.get(name)
+    }
+    pub fn activate(&mut self, name: &str) {
+        if !self.is_active(name) {
+            self.active.push(name.to_string());
+        }
+    }
+    pub fn end(&mut self, name: &str) {
+        self.active.retain(|n| n != name);
+    }
+    pub fn is_active(&self, name: &str) -> bool {
+        self.active.iter().any(|n| n == name)
+    }
+    /// effect of the active overrides on the messages from sender to target, None when no override applies
+    pub fn get_effect(&self, sender: ProcessId, target: ProcessId) -> Option<LinkEffect> {
+        let mut effect: Option<LinkEffect> = None;
+        for o in self.active.iter().filter_map(|name| self.declared.get(name)) {
+            if !o.matches(sender, target) {
+                continue;
+            }
+            let e = effect.get_or_insert(LinkEffect { latency_factor: 1.0, extra_latency: 0, loss: 0.0 });
+            e.latency_factor *= o.latency_factor;
+            e.extra_latency += o.extra_latency;
+            e.loss = 1.0 - (1.0 - e.loss) * (1.0 - o.loss);
+        }
+        effect
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::simulation::overrides::{NetworkOverride, NetworkOverrides, LinkEffect};
+
+    #[test]
+    fn test_overrides() {
+        let slow = NetworkOverride { processes: vec![3], latency_factor: 2.0, ..Default::default() };
+        let link = NetworkOverride { links: vec![(1, 3)], directed: true, extra_latency: 50, loss: 0.5, ..Default::default() };
+        let all = NetworkOverride { loss: 0.5, ..Default::default() };
+        assert!(slow.matches(3, 0) && slow.matches(0, 3) && !slow.matches(0, 1));
+        assert!(link.matches(1, 3) && !link.matches(3, 1));
+        assert!(all.matches(7, 8));
+
+        let mut overrides = NetworkOverrides::default();
+        overrides.declare("slow".to_string(), slow);
+        overrides.declare("link".to_string(), link);
+        overrides.declare("all".to_string(), all);
+        assert_eq!(overrides.get_effect(1, 3), None);
+
+        overrides.activate("slow");
+        overrides.activate("link");
+        overrides.activate("link");
+        assert_eq!(overrides.get_effect(0, 1), None);
+        assert_eq!(overrides.get_effect(3, 1), Some(LinkEffect { latency_factor: 2.0, extra_latency: 0, loss: 0.0 }));
+        let effect = overrides.get_effect(1, 3).unwrap();
+        assert_eq!(effect, LinkEffect { latency_factor: 2.0, extra_latency: 50, loss: 0.5 });
+        assert_eq!(effect.apply(100), 250);
+
+        overrides.activate("all");
+        assert_eq!(overrides.get_effect(1, 3).unwrap().loss, 0.75);
+
+        overrides.end("link");
+        overrides.end("all");
+        assert!(!overrides.is_active("link"));
+        assert_eq!(overrides.get_effect(1, 3), Some(LinkEffect { latency_factor: 2.0, extra_latency: 0, loss: 0.0 }));
+    }
+}
@@ -0,0 +1,48 @@
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0>.
+// This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use serde::{Serialize, Deserialize};
+
+use crate::simulation::ProcessId;
+
+/// Groups of processes that are unable to communicate with each other, set by the partition and heal churn actions.
+/// While partitioned, the messages sent between processes of different groups are dropped.
+#[cfg_attr(feature = "checkpointing", derive(Serialize, Deserialize))]
+#[derive(Debug, Default)]
+pub struct Partition {
+    /// group of each process, the processes that are not in it are all in group num_groups
+    groups: Vec<u32>,
+    /// 0 when there is no partition
+    num_groups: u32
+}
+
+impl Partition {
+    /// splits the processes into groups, plus one more group with the processes that are not in any of them
+    pub fn split(&mut self, groups: &[Vec<ProcessId>]) {
+        let num_groups = groups.len() as u32;
+        let len = groups.iter().flat_map(|g| g.iter()).max().map_or(0, |&id| id as usize + 1);
+        self.groups = vec![num_groups; len];
+        for (group, ids) in groups.iter().enumerate() {
+            for &id in ids {
+                self.groups[id as usize] = group as u32;
+            }
+        }
+        self.num_groups = num_groups;
+    }
+    pub fn heal(&mut self) {
+        self.groups = Vec::new();
+        self.num_groups = 0;
+    }
+    pub fn is_partitioned(&self) -> bool {
+        self.num_groups > 0
+    }
+    fn get_group(&self, id: ProcessId) -> u32 {
+        self.groups.get(id as usize).cloned().unwrap_or(self.num_groups)
+    }
+    /// true when messages from sender can reach target
+    pub fn connected(&self, sender: ProcessId, target: ProcessId) -> bool {
+        !self.is_partitioned() || self.get_group(sender) == self.get_group(target)
+    }
+}
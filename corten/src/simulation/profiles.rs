@@ -0,0 +1,135 @@
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0>.
+// This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use serde::{Serialize, Deserialize};
+
+use crate::simulation::{Conf, Time, ProcessId};
+use crate::simulation::asynchrony::{Asynchrony, NoAsynchrony};
+use crate::simulation::selection::ProcessSelection;
+use crate::simulation::utils;
+
+/// Asynchrony and op_duration of some of the processes, for e.g. stragglers that are slower than the others.
+/// Profiles are given by async_profiles in the configuration, and the profile of processes can be changed
+/// by the profile churn action, where the profile named default goes back to asynchrony and op_duration of the configuration.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct AsyncProfile {
+    pub name: String,
+    /// by default the asynchrony of the configuration
+    #[serde(default)]
+    pub asynchrony: Option<Box<dyn Asynchrony>>,
+    /// by default the op_duration of the configuration
+    #[serde(default)]
+    pub op_duration: Option<Time>,
+    /// multiplies op_duration
+    #[serde(default = "op_duration_factor_default")]
+    pub op_duration_factor: f64,
+    /// processes that have the profile from the start of the simulation, for e.g. {random: 0.1}
+    #[serde(default)]
+    pub processes: Option<ProcessSelection>
+}
+
+fn op_duration_factor_default() -> f64 {
+    1.0
+}
+
+impl AsyncProfile {
+    pub fn new(name: &str, asynchrony: Option<Box<dyn Asynchrony>>, op_duration_factor: f64, processes: Option<ProcessSelection>) -> Self {
+        AsyncProfile { name: name.to_string(), asynchrony, op_duration: None, op_duration_factor, processes }
+    }
+}
+
+/// a profile churn action, {name: profile, processes: selection}
+#[derive(Deserialize, Debug)]
+pub struct ProfileAssignment {
+    pub name: String,
+    pub processes: ProcessSelection
+}
+
+/// the name of the profile with the asynchrony and op_duration of the configuration
+pub const DEFAULT_PROFILE: &str = "default";
+
+/// Profiles known to the kernel, with the op_duration of each one already computed
+#[derive(Debug, Default)]
+pub struct AsyncProfiles {
+    profiles: Vec<(AsyncProfile, Time)>,
+    default_asynchrony: Option<Box<dyn Asynchrony>>,
+    default_op_duration: Time
+}
+
+impl AsyncProfiles {
+    pub fn new(conf: &Conf, default_op_duration: Time) -> Result<Self, String> {
+        let mut profiles = Vec::new();
+        for profile in &conf.async_profiles {
+            if profile.name == DEFAULT_PROFILE || profiles.iter().any(|(p, _): &(AsyncProfile, Time)| p.name == profile.name) {
+                return Err(format!("async profile {} is defined more than once, or uses the reserved name {}", profile.name, DEFAULT_PROFILE));
+            }
+            let asynchrony = profile.asynchrony.as_ref().unwrap_or(&conf.asynchrony);
+            let no_async = asynchrony.as_any().downcast_ref::<NoAsynchrony>().is_some();
+            let op_duration = match profile.op_duration.or(conf.op_duration) {
+                Some(op_duration) => (op_duration as f64 * profile.op_duration_factor).round() as Time,
+                None if no_async => 0,
+                None => return Err(format!("async profile {} has asynchrony, so it is mandatory to provide op_duration in the profile or in the configuration", profile.name))
+            };
+            profiles.push((profile.clone(), op_duration));
+        }
+        Ok(AsyncProfiles { profiles, default_asynchrony: Some(conf.asynchrony.clone()), default_op_duration })
+    }
+    pub fn exists(&self, name: &str) -> bool {
+        name == DEFAULT_PROFILE || self.profiles.iter().any(|(p, _)| p.name == name)
+    }
+    /// asynchrony and op_duration of the processes with the profile name, None if the profile does not exist
+    pub fn get(&self, name: &str) -> Option<(Box<dyn Asynchrony>, Time)> {
+        let default_asynchrony = || self.default_asynchrony.clone().unwrap_or_else(|| Box::new(NoAsynchrony::new()));
+        if name == DEFAULT_PROFILE {
+            return Some((default_asynchrony(), self.default_op_duration));
+        }
+        self.profiles.iter()
+            .find(|(p, _)| p.name == name)
+            .map(|(p, op_duration)| (p.asynchrony.clone().unwrap_or_else(default_asynchrony), *op_duration))
+    }
+    /// processes of each profile at the start of the simulation, where seed is the seed of the configuration
+    pub fn initial_assignments(&self, n: ProcessId, seed: u64) -> Result<Vec<(String, Vec<ProcessId>)>, String> {
+        let mut assignments = Vec::new();
+        for (index, (profile, _)) in self.profiles.iter().enumerate() {
+            if let Some(processes) = &profile.processes {
+                processes.check(n)?;
+                let ids = processes.resolve(n, &mut 0, utils::mix64(seed ^ utils::mix64(index as u64)));
+                assignments.push((profile.name.clone(), ids));
+            }
+        }
+        Ok(assignments)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::simulation::Conf;
+    use crate::simulation::asynchrony::UniformAsynchrony;
+    use crate::simulation::profiles::{AsyncProfile, AsyncProfiles};
+    use crate::simulation::selection::ProcessSelection;
+
+    #[test]
+    fn test_async_profiles() {
+        let mut conf = Conf::new(10);
+        conf.op_duration = Some(100);
+        conf.async_profiles.push(AsyncProfile::new("slow", None, 5.0, Some(ProcessSelection::Random(0.2))));
+        let profiles = AsyncProfiles::new(&conf, 100).unwrap();
+        assert!(profiles.exists("slow") && profiles.exists("default") && !profiles.exists("fast"));
+        assert_eq!(profiles.get("slow").unwrap().1, 500);
+        assert_eq!(profiles.get("default").unwrap().1, 100);
+        let assignments = profiles.initial_assignments(10, 0).unwrap();
+        assert_eq!(assignments.len(), 1);
+        assert_eq!(assignments[0].1.len(), 2);
+        conf.async_profiles[0].processes = Some(ProcessSelection::List(vec![10]));
+        assert!(AsyncProfiles::new(&conf, 100).unwrap().initial_assignments(10, 0).is_err());
+
+        conf.async_profiles.push(AsyncProfile::new("slow", None, 2.0, None));
+        assert!(AsyncProfiles::new(&conf, 100).is_err());
+
+        let mut conf = Conf::new(10);
+        conf.async_profiles.push(AsyncProfile::new("jittery", Some(Box::new(UniformAsynchrony::new(0.0, 1.0))), 1.0, None));
+        assert!(AsyncProfiles::new(&conf, 0).is_err());
+    }
+}
@@ -0,0 +1,102 @@
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0>.
+// This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::rc::Rc;
+use std::cell::RefCell;
+
+use rand_xorshift::XorShiftRng;
+
+use serde::{Serialize, Deserialize};
+
+use crate::simulation::{ApplicationBase, Operation, Process, ProcessId, TimerId};
+
+/// Identifies a request among the requests made by the same process
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Serialize, Deserialize)]
+pub struct RequestId(pub(crate) u64);
+
+/// A request received by a process, given by Process::get_request while the process handles it.
+/// It can be kept, for e.g. in another operation, to reply only later with Process::reply.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct RequestContext {
+    pub id: RequestId,
+    /// the process that made the request, to which the reply is sent
+    pub sender: ProcessId
+}
+
+/// A request that is waiting for its reply or timeout
+#[cfg_attr(feature = "checkpointing", derive(Serialize, Deserialize))]
+#[derive(Debug)]
+pub(crate) struct PendingRequest {
+    pub timer: TimerId,
+    pub on_reply: Rc<dyn Operation>
+}
+
+/// the message with the request, that executes op in the context of the request
+#[cfg_attr(feature = "checkpointing", derive(Serialize, Deserialize))]
+#[derive(Debug)]
+pub(crate) struct RpcRequest {
+    pub request: RequestContext,
+    pub op: Box<dyn Operation>
+}
+
+#[cfg_attr(feature = "checkpointing", typetag::serde)]
+impl Operation for RpcRequest {
+    fn invoke(&self, app: Rc<RefCell<Box<dyn ApplicationBase>>>, process: Rc<RefCell<Process>>) {
+        let previous = process.borrow().request.replace(Some(self.request));
+        self.op.invoke(app, process.clone());
+        process.borrow().request.set(previous);
+    }
+    fn corrupt(&self, rng: &mut XorShiftRng) -> Option<Box<dyn Operation>> {
+        self.op.corrupt(rng).map(|op| Box::new(RpcRequest { request: self.request, op }) as Box<dyn Operation>)
+    }
+    fn size(&self) -> u64 {
+        self.op.size()
+    }
+}
+
+/// the message with the reply, that executes op and then the continuation of the request,
+/// unless the request already timed out or was answered
+#[cfg_attr(feature = "checkpointing", derive(Serialize, Deserialize))]
+#[derive(Debug)]
+pub(crate) struct RpcReply {
+    pub id: RequestId,
+    pub op: Box<dyn Operation>
+}
+
+#[cfg_attr(feature = "checkpointing", typetag::serde)]
+impl Operation for RpcReply {
+    fn invoke(&self, app: Rc<RefCell<Box<dyn ApplicationBase>>>, process: Rc<RefCell<Process>>) {
+        let pending = process.borrow().requests.borrow_mut().remove(&self.id);
+        if let Some(pending) = pending {
+            process.borrow().cancel(pending.timer);
+            self.op.invoke(app.clone(), process.clone());
+            pending.on_reply.invoke(app, process);
+        }
+    }
+    fn corrupt(&self, rng: &mut XorShiftRng) -> Option<Box<dyn Operation>> {
+        self.op.corrupt(rng).map(|op| Box::new(RpcReply { id: self.id, op }) as Box<dyn Operation>)
+    }
+    fn size(&self) -> u64 {
+        self.op.size()
+    }
+}
+
+/// the local call that executes op when the reply does not arrive in time
+#[cfg_attr(feature = "checkpointing", derive(Serialize, Deserialize))]
+#[derive(Debug)]
+pub(crate) struct RpcTimeout {
+    pub id: RequestId,
+    pub op: Box<dyn Operation>
+}
+
+#[cfg_attr(feature = "checkpointing", typetag::serde)]
+impl Operation for RpcTimeout {
+    fn invoke(&self, app: Rc<RefCell<Box<dyn ApplicationBase>>>, process: Rc<RefCell<Process>>) {
+        let pending = process.borrow().requests.borrow_mut().remove(&self.id);
+        if pending.is_some() {
+            self.op.invoke(app, process);
+        }
+    }
+}
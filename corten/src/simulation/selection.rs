@@ -0,0 +1,252 @@
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0>.
+// This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::convert::TryFrom;
+
+use rand::prelude::*;
+use rand_xorshift::XorShiftRng;
+
+use serde::{Serialize, Deserialize};
+use yaml_rust::yaml::Yaml;
+
+use crate::simulation::ProcessId;
+use crate::simulation::utils;
+
+/// A set of processes, given in a churn file or in the configuration as:
+/// - a process id, for e.g. 3
+/// - a list of process ids, for e.g. [1, 4, 7]
+/// - a range of process ids "first..last", where last is excluded, for e.g. "0..50"
+/// - a fraction of n, for e.g. 0.25, which takes the following processes by order of id
+/// - a fraction of n taken at random, for e.g. {random: 0.25}
+/// - each process independently with a probability, for e.g. {probability: 0.25}
+///
+/// The random selections are taken from a seed, so that they do not depend on the Random Number Generator of the simulation.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(try_from = "SelectionRepr", into = "SelectionRepr")]
+pub enum ProcessSelection {
+    List(Vec<ProcessId>),
+    Range(ProcessId, ProcessId),
+    Fraction(f64),
+    Random(f64),
+    Probability(f64)
+}
+
+/// a ProcessSelection as it is written in the configuration
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum SelectionRepr {
+    Id(ProcessId),
+    List(Vec<ProcessId>),
+    Range(String),
+    Fraction(f64),
+    Random { random: f64 },
+    Probability { probability: f64 }
+}
+
+impl TryFrom<SelectionRepr> for ProcessSelection {
+    type Error = String;
+
+    fn try_from(repr: SelectionRepr) -> Result<Self, String> {
+        match repr {
+            SelectionRepr::Id(id) => Ok(ProcessSelection::List(vec![id])),
+            SelectionRepr::List(ids) => Ok(ProcessSelection::List(ids)),
+            SelectionRepr::Range(range) => parse_range(&range).map(|(first, last)| ProcessSelection::Range(first, last)),
+            SelectionRepr::Fraction(fraction) => check_fraction(fraction).map(ProcessSelection::Fraction),
+            SelectionRepr::Random { random } => check_fraction(random).map(ProcessSelection::Random),
+            SelectionRepr::Probability { probability } => check_fraction(probability).map(ProcessSelection::Probability)
+        }
+    }
+}
+
+impl From<ProcessSelection> for SelectionRepr {
+    fn from(selection: ProcessSelection) -> Self {
+        match selection {
+            ProcessSelection::List(ids) => SelectionRepr::List(ids),
+            ProcessSelection::Range(first, last) => SelectionRepr::Range(format!("{}..{}", first, last)),
+            ProcessSelection::Fraction(fraction) => SelectionRepr::Fraction(fraction),
+            ProcessSelection::Random(random) => SelectionRepr::Random { random },
+            ProcessSelection::Probability(probability) => SelectionRepr::Probability { probability }
+        }
+    }
+}
+
+fn parse_range(range: &str) -> Result<(ProcessId, ProcessId), String> {
+    let bounds: Vec<&str> = range.split("..").collect();
+    let parse = |bound: &str| bound.trim().parse::<ProcessId>().map_err(|_| format!("{} is not a range of process ids, for e.g. \"0..50\"", range));
+    if bounds.len() != 2 {
+        return Err(format!("{} is not a range of process ids, for e.g. \"0..50\"", range));
+    }
+    let (first, last) = (parse(bounds[0])?, parse(bounds[1])?);
+    if first > last {
+        return Err(format!("range {} must not end before it starts", range));
+    }
+    Ok((first, last))
+}
+
+fn check_fraction(fraction: f64) -> Result<f64, String> {
+    if (0.0..=1.0).contains(&fraction) {
+        Ok(fraction)
+    } else {
+        Err("a fraction or a probability of processes must be between 0.0 and 1.0".to_string())
+    }
+}
+
+impl ProcessSelection {
+    /// n is the number of processes, and all the ids must be in 0..n
+    pub fn from_yaml(yaml: &Yaml, n: ProcessId) -> Result<Self, String> {
+        let check_id = |id: i64| {
+            if id >= 0 && id < n as i64 {
+                Ok(id as ProcessId)
+            } else {
+                Err(format!("process id {} does not exist, ids must be in 0..{}", id, n))
+            }
+        };
+
+        match yaml {
+            Yaml::Integer(id) => Ok(ProcessSelection::List(vec![check_id(*id)?])),
+            Yaml::Array(ids) => {
+                let ids = ids.iter()
+                    .map(|id| id.as_i64().ok_or_else(|| "a list of processes must contain only process ids (integers)".to_string()).and_then(check_id))
+                    .collect::<Result<Vec<ProcessId>, String>>()?;
+                Ok(ProcessSelection::List(ids))
+            },
+            Yaml::String(range) => {
+                let selection = parse_range(range).map(|(first, last)| ProcessSelection::Range(first, last))?;
+                selection.check(n)?;
+                Ok(selection)
+            },
+            Yaml::Real(_) => {
+                let fraction = yaml.as_f64().ok_or_else(|| format!("{:?} is not a fraction of processes", yaml))?;
+                check_fraction(fraction).map(ProcessSelection::Fraction)
+            },
+            Yaml::Hash(hash) if hash.len() == 1 => {
+                match (yaml["random"].as_f64(), yaml["probability"].as_f64()) {
+                    (Some(random), None) => check_fraction(random).map(ProcessSelection::Random),
+                    (None, Some(probability)) => check_fraction(probability).map(ProcessSelection::Probability),
+                    _ => Err(format!("{}, where random and probability must be real numbers", USAGE))
+                }
+            },
+            _ => Err(USAGE.to_string())
+        }
+    }
+    /// checks that the ids of a list or a range are in 0..n
+    pub fn check(&self, n: ProcessId) -> Result<(), String> {
+        match self {
+            ProcessSelection::List(ids) => match ids.iter().find(|&&id| id >= n) {
+                Some(id) => Err(format!("process id {} does not exist, ids must be in 0..{}", id, n)),
+                None => Ok(())
+            },
+            ProcessSelection::Range(first, last) if *last > n => Err(format!("range {}..{} must be within 0..{}", first, last, n)),
+            _ => Ok(())
+        }
+    }
+    /// returns the ids of the selected processes, where the random selections are taken from seed
+    /// a fraction takes the processes that follow next, which is advanced past them
+    pub fn resolve(&self, n: ProcessId, next: &mut ProcessId, seed: u64) -> Vec<ProcessId> {
+        let mut rng = XorShiftRng::seed_from_u64(seed);
+        match self {
+            ProcessSelection::List(ids) => ids.clone(),
+            ProcessSelection::Range(first, last) => (*first..*last).collect(),
+            ProcessSelection::Fraction(fraction) => {
+                let first = *next;
+                let last = (first + (fraction * n as f64).round() as ProcessId).min(n);
+                *next = last;
+                (first..last).collect()
+            },
+            ProcessSelection::Random(fraction) => {
+                let num_proc = (fraction * n as f64).round() as usize;
+                let mut ids: Vec<ProcessId> = (0..n).collect::<Vec<ProcessId>>().choose_multiple(&mut rng, num_proc).cloned().collect();
+                ids.sort();
+                ids
+            },
+            ProcessSelection::Probability(probability) => {
+                (0..n).filter(|_| rng.gen::<f64>() < *probability).collect()
+            }
+        }
+    }
+}
+
+const USAGE: &str = "expected processes as an id, a list of ids, a range \"first..last\", a fraction of n, {random: fraction} or {probability: p}";
+
+/// resolves each selection into a group of process ids, which must not overlap
+/// fractions take consecutive ids starting at 0, so [0.5, 0.5] splits the processes in two halves
+pub fn resolve_groups(selections: &[ProcessSelection], n: ProcessId, seed: u64) -> Result<Vec<Vec<ProcessId>>, String> {
+    let mut next = 0;
+    let mut in_group = vec![false; n as usize];
+    let mut groups = Vec::with_capacity(selections.len());
+    for (index, selection) in selections.iter().enumerate() {
+        let group = selection.resolve(n, &mut next, utils::mix64(seed ^ utils::mix64(index as u64)));
+        for &id in &group {
+            if in_group[id as usize] {
+                return Err(format!("process {} is in more than one group", id));
+            }
+            in_group[id as usize] = true;
+        }
+        groups.push(group);
+    }
+    Ok(groups)
+}
+
+#[cfg(test)]
+mod test {
+    use yaml_rust::YamlLoader;
+
+    use crate::simulation::selection::{ProcessSelection, resolve_groups};
+
+    fn parse(s: &str, n: u32) -> Result<ProcessSelection, String> {
+        ProcessSelection::from_yaml(&YamlLoader::load_from_str(s).unwrap()[0], n)
+    }
+
+    #[test]
+    fn test_process_selection() {
+        assert_eq!(parse("3", 10), Ok(ProcessSelection::List(vec![3])));
+        assert_eq!(parse("[1, 4, 7]", 10), Ok(ProcessSelection::List(vec![1, 4, 7])));
+        assert_eq!(parse("\"2..5\"", 10), Ok(ProcessSelection::Range(2, 5)));
+        assert_eq!(parse("0.25", 10), Ok(ProcessSelection::Fraction(0.25)));
+        assert!(parse("10", 10).is_err());
+        assert!(parse("[1, -1]", 10).is_err());
+        assert!(parse("\"5..11\"", 10).is_err());
+        assert!(parse("\"5..2\"", 10).is_err());
+        assert!(parse("1.5", 10).is_err());
+        assert!(parse("join", 10).is_err());
+        assert_eq!(parse("{random: 0.25}", 10), Ok(ProcessSelection::Random(0.25)));
+        assert_eq!(parse("{probability: 0.5}", 10), Ok(ProcessSelection::Probability(0.5)));
+        assert!(parse("{random: 1.5}", 10).is_err());
+        assert!(parse("{random: 0.1, probability: 0.1}", 10).is_err());
+
+        let groups = resolve_groups(&[ProcessSelection::Fraction(0.5), ProcessSelection::Fraction(0.3)], 10, 0).unwrap();
+        assert_eq!(groups, vec![vec![0, 1, 2, 3, 4], vec![5, 6, 7]]);
+
+        let groups = resolve_groups(&[ProcessSelection::Range(6, 8), ProcessSelection::List(vec![9, 0])], 10, 0).unwrap();
+        assert_eq!(groups, vec![vec![6, 7], vec![9, 0]]);
+
+        assert!(resolve_groups(&[ProcessSelection::Fraction(0.5), ProcessSelection::List(vec![4])], 10, 0).is_err());
+    }
+
+    #[test]
+    fn test_random_selection() {
+        let ids = ProcessSelection::Random(0.25).resolve(100, &mut 0, 1);
+        assert_eq!(ids.len(), 25);
+        assert_eq!(ids, ProcessSelection::Random(0.25).resolve(100, &mut 0, 1));
+        assert_ne!(ids, ProcessSelection::Random(0.25).resolve(100, &mut 0, 2));
+
+        let ids = ProcessSelection::Probability(0.5).resolve(1000, &mut 0, 1);
+        assert!(ids.len() > 400 && ids.len() < 600);
+        assert!(ids.windows(2).all(|w| w[0] < w[1]));
+
+        // the configuration has the same forms as a churn file
+        let selection: ProcessSelection = serde_yaml::from_str("{random: 0.1}").unwrap();
+        assert_eq!(selection, ProcessSelection::Random(0.1));
+        let selection: ProcessSelection = serde_yaml::from_str("[1, 2]").unwrap();
+        assert_eq!(selection, ProcessSelection::List(vec![1, 2]));
+        let selection: ProcessSelection = serde_yaml::from_str("\"2..5\"").unwrap();
+        assert_eq!(selection, ProcessSelection::Range(2, 5));
+        assert_eq!(serde_yaml::from_str::<ProcessSelection>("3").unwrap(), ProcessSelection::List(vec![3]));
+        assert_eq!(serde_yaml::from_str::<ProcessSelection>("0.5").unwrap(), ProcessSelection::Fraction(0.5));
+        assert!(serde_yaml::from_str::<ProcessSelection>("{probability: 2.0}").is_err());
+        assert!(ProcessSelection::List(vec![10]).check(10).is_err());
+        assert!(ProcessSelection::Range(5, 11).check(10).is_err());
+    }
+}
@@ -0,0 +1,94 @@
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0>.
+// This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::cmp;
+
+use rand::Rng;
+
+use serde::{Serialize, Deserialize};
+
+use crate::simulation::Time;
+use crate::simulation::wrappers::{WeibullWrapper, ExpWrapper, ParetoWrapper, LogNormalWrapper, DistributionWrapper};
+
+/// Distribution of the length of the sessions, in units of simulation time, for e.g. {exponential: {lambda: 0.001}}
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "kebab-case")]
+pub enum SessionLength {
+    Weibull(WeibullWrapper),
+    Exponential(ExpWrapper),
+    Pareto(ParetoWrapper),
+    LogNormal(LogNormalWrapper)
+}
+
+impl SessionLength {
+    /// sessions last at least one unit of time
+    pub fn sample<R: Rng>(&self, rng: &mut R) -> Time {
+        let length = match self {
+            SessionLength::Weibull(weibull) => weibull.sample(rng),
+            SessionLength::Exponential(exp) => exp.sample(rng),
+            SessionLength::Pareto(pareto) => pareto.sample(rng),
+            SessionLength::LogNormal(log_normal) => log_normal.sample(rng)
+        };
+        cmp::max(1, length.round() as Time)
+    }
+}
+
+/// Churn in which each process alternates between sessions in which it is up and sessions in which it is down,
+/// given in a churn file by the sessions action:
+/// [time, sessions, {processes: "0..50", up: {weibull: {scale: 3600000, shape: 0.5}}, down: {exponential: {lambda: 0.0001}}}]
+/// From time on, the processes are in an up session, and when it ends they fail (or leave) and recover after a down session.
+/// The processes must have joined by time, as those that did not join are left out of the sessions.
+/// The lengths of the sessions are drawn from the Random Number Generator of the simulation when each session starts.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SessionGenerator {
+    pub up: SessionLength,
+    pub down: SessionLength,
+    /// no process fails or recovers after until, by default the sessions go on until the end of the simulation
+    #[serde(default)]
+    pub until: Option<Time>,
+    /// the processes leave at the end of an up session, instead of failing
+    #[serde(default)]
+    pub leave: bool
+}
+
+impl SessionGenerator {
+    /// time at which the session that starts at ts ends, None if that is after until
+    pub fn next_change<R: Rng>(&self, ts: Time, up: bool, rng: &mut R) -> Option<Time> {
+        let length = if up { &self.up } else { &self.down };
+        let next = ts + length.sample(rng);
+        match self.until {
+            Some(until) if next > until => None,
+            _ => Some(next)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rand::prelude::*;
+    use rand_xorshift::XorShiftRng;
+
+    use crate::simulation::sessions::{SessionGenerator, SessionLength};
+
+    #[test]
+    fn test_session_generator() {
+        let generator: SessionGenerator = serde_yaml::from_str("{up: {pareto: {scale: 100, shape: 2}}, down: {exponential: {lambda: 0.01}}, until: 10000}").unwrap();
+        assert!(!generator.leave);
+        let mut rng = XorShiftRng::seed_from_u64(0);
+        let ups: Vec<i64> = (0..1000).map(|_| generator.up.sample(&mut rng)).collect();
+        assert!(ups.iter().all(|&up| up >= 100));
+        let downs: Vec<i64> = (0..1000).map(|_| generator.down.sample(&mut rng)).collect();
+        let mean = downs.iter().sum::<i64>() as f64 / downs.len() as f64;
+        assert!(mean > 80.0 && mean < 120.0);
+
+        assert!(generator.next_change(9999, true, &mut rng).is_none());
+        let next = generator.next_change(0, true, &mut rng).unwrap();
+        assert!(next >= 100);
+
+        let length: SessionLength = serde_yaml::from_str("log-normal: {mean: 0.0, std_dev: 0.1}").unwrap();
+        assert_eq!(length.sample(&mut rng), 1);
+        assert!(serde_yaml::from_str::<SessionLength>("gamma: {shape: 1}").is_err());
+    }
+}
@@ -0,0 +1,84 @@
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0>.
+// This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use serde::{Serialize, Deserialize};
+
+use crate::simulation::Time;
+
+/// What one unit of simulation Time stands for, given by time_unit in the configuration.
+/// Durations written with a unit, for e.g. "150ms" or "30d" in churn files,
+/// and latency files with a latency_unit are converted to it.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TimeUnit {
+    Ns,
+    Us,
+    #[default]
+    Ms,
+    S
+}
+
+impl TimeUnit {
+    /// number of nanoseconds in one unit
+    pub fn nanos(&self) -> i64 {
+        match self {
+            TimeUnit::Ns => 1,
+            TimeUnit::Us => 1_000,
+            TimeUnit::Ms => 1_000_000,
+            TimeUnit::S => 1_000_000_000
+        }
+    }
+    /// converts t, expressed in this unit, to the unit to, rounding to the nearest integer
+    pub fn convert(&self, t: f64, to: TimeUnit) -> Time {
+        (t * self.nanos() as f64 / to.nanos() as f64).round() as Time
+    }
+    /// parses a duration such as "250ms", "1.5s" or "30d" and returns it in this unit
+    /// the accepted units are ns, us, ms, s, min, h and d, and a number without unit is already in this unit
+    pub fn parse_duration(&self, s: &str) -> Result<Time, String> {
+        let s = s.trim();
+        let split = s.find(|c: char| !(c.is_ascii_digit() || c == '.' || c == '-' || c == '+')).unwrap_or(s.len());
+        let (number, unit) = s.split_at(split);
+        let value = number.parse::<f64>().map_err(|_| format!("{} is not a duration, expected a number followed by a unit (ns, us, ms, s, min, h or d)", s))?;
+
+        let nanos = match unit.trim() {
+            "" => self.nanos() as f64,
+            "ns" => 1.0,
+            "us" => 1e3,
+            "ms" => 1e6,
+            "s" => 1e9,
+            "min" => 60.0 * 1e9,
+            "h" => 3600.0 * 1e9,
+            "d" => 86400.0 * 1e9,
+            other => return Err(format!("unknown time unit {} in {}, expected ns, us, ms, s, min, h or d", other, s))
+        };
+
+        let t = (value * nanos / self.nanos() as f64).round();
+        if t.abs() >= Time::MAX as f64 {
+            return Err(format!("{} does not fit in the simulation time", s));
+        }
+        Ok(t as Time)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::simulation::time::TimeUnit;
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(TimeUnit::Ms.parse_duration("250"), Ok(250));
+        assert_eq!(TimeUnit::Ms.parse_duration("1.5s"), Ok(1500));
+        assert_eq!(TimeUnit::Ms.parse_duration("30d"), Ok(30 * 24 * 3600 * 1000));
+        assert_eq!(TimeUnit::Us.parse_duration("2 min"), Ok(120_000_000));
+        assert_eq!(TimeUnit::S.parse_duration("400ms"), Ok(0));
+        assert_eq!(TimeUnit::Ns.parse_duration("3us"), Ok(3000));
+        assert!(TimeUnit::Ms.parse_duration("3 weeks").is_err());
+        assert!(TimeUnit::Ms.parse_duration("ms").is_err());
+        assert!(TimeUnit::Ns.parse_duration("1000000d").is_err());
+
+        assert_eq!(TimeUnit::Ms.convert(12.345, TimeUnit::Us), 12345);
+        assert_eq!(TimeUnit::Ns.convert(1e9, TimeUnit::S), 1);
+    }
+}
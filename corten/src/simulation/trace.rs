@@ -0,0 +1,248 @@
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0>.
+// This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+
+use serde::{Serialize, Deserialize};
+
+use crate::Error;
+use crate::simulation::{Time, TimeUnit, ProcessId};
+
+/// Format of an availability trace
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum TraceFormat {
+    /// one line per interval in which a node is up: node, start and end separated by '\t'
+    #[default]
+    Intervals,
+    /// event trace of the Failure Trace Archive, one line per event with the columns
+    /// event_id, component_id, node_id, platform_id, node_name, event_type, event_start_time, event_end_time, ...
+    /// separated by '\t', where event_type is 1 for availability and 0 for unavailability
+    Fta
+}
+
+/// Churn that replays an availability trace, given in a churn file by the trace action:
+/// [time, trace, {file: traces/nodes.tab, format: fta, time_unit: s, repeat: true, until: 604800000}]
+/// The nodes of the trace are mapped to the processes first_process, first_process + 1, ... in the order in
+/// which they first appear in the file, and the start of the trace is mapped to time.
+/// The processes must be up when the trace starts, they fail (or leave) when their node becomes unavailable
+/// and recover when it becomes available again.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TraceConf {
+    pub file: String,
+    #[serde(default)]
+    pub format: TraceFormat,
+    /// unit of the times in the trace, by default the time unit of the simulation
+    #[serde(default)]
+    pub time_unit: Option<TimeUnit>,
+    /// the times in the trace are multiplied by time_scale, for e.g. 0.5 replays the trace twice as fast
+    #[serde(default = "default_time_scale")]
+    pub time_scale: f64,
+    #[serde(default)]
+    pub first_process: ProcessId,
+    /// the trace starts over when it ends, until is then required
+    #[serde(default)]
+    pub repeat: bool,
+    /// no process fails or recovers after until
+    #[serde(default)]
+    pub until: Option<Time>,
+    /// the processes leave when their node becomes unavailable, instead of failing
+    #[serde(default)]
+    pub leave: bool
+}
+
+fn default_time_scale() -> f64 {
+    1.0
+}
+
+/// A process that becomes up or down at ts
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Transition {
+    pub ts: Time,
+    pub id: ProcessId,
+    pub up: bool
+}
+
+/// a period of time in which a node is up or down, in the units of the trace
+struct Record {
+    node: String,
+    start: f64,
+    end: f64,
+    up: bool
+}
+
+impl TraceConf {
+    /// transitions of the n processes of a simulation with the given time unit when the trace starts at start,
+    /// sorted by time, that only include the changes of status of each process
+    pub fn get_transitions(&self, start: Time, time_unit: TimeUnit, n: ProcessId) -> Result<Vec<Transition>, Error> {
+        if self.time_scale <= 0.0 {
+            return Err(Error::config(self.file.clone(), None, format!("time_scale must be positive, but it is {}", self.time_scale)));
+        }
+        if self.repeat && self.until.is_none() {
+            return Err(Error::config(self.file.clone(), None, "a trace that repeats requires until"));
+        }
+        let records = self.read_records()?;
+        let (nodes, ids) = self.map_nodes(&records, n)?;
+        let t_min = records.iter().map(|r| r.start).fold(f64::INFINITY, f64::min);
+        let t_max = records.iter().map(|r| r.end).fold(f64::NEG_INFINITY, f64::max);
+
+        let trace_unit = self.time_unit.unwrap_or(time_unit);
+        let to_time = |t: f64| start + trace_unit.convert((t - t_min) * self.time_scale, time_unit);
+        let period = to_time(t_max) - start;
+        if self.repeat && period <= 0 {
+            return Err(Error::config(self.file.clone(), None, "a trace that repeats must last at least one unit of time"));
+        }
+        let until = self.until.unwrap_or(Time::MAX);
+
+        let mut by_node: Vec<Vec<(Time, bool)>> = vec![Vec::new(); nodes];
+        for record in &records {
+            let changes = &mut by_node[ids[&record.node]];
+            let (from, to) = (to_time(record.start), to_time(record.end));
+            let mut offset = 0;
+            while from + offset <= until {
+                changes.push((from + offset, record.up));
+                if to + offset <= until {
+                    changes.push((to + offset, !record.up));
+                }
+                if !self.repeat {
+                    break;
+                }
+                offset += period;
+            }
+        }
+
+        let mut transitions = Vec::new();
+        for (node, changes) in by_node.iter_mut().enumerate() {
+            let id = self.first_process + node as ProcessId;
+            // at the same time, a node that becomes both up and down stays up, as when an interval starts where another ends
+            changes.sort_by_key(|&(ts, up)| (ts, up));
+            let initial = match changes.first() {
+                Some(&(ts, up)) if ts > start => Some(!up),
+                _ => None
+            };
+
+            let mut current = true;
+            if let Some(up) = initial {
+                if up != current {
+                    transitions.push(Transition { ts: start, id, up });
+                    current = up;
+                }
+            }
+            for (index, &(ts, up)) in changes.iter().enumerate() {
+                let last_at_ts = changes.get(index + 1).is_none_or(|&(next, _)| next != ts);
+                if last_at_ts && up != current {
+                    transitions.push(Transition { ts, id, up });
+                    current = up;
+                }
+            }
+        }
+        transitions.sort_by_key(|t| t.ts);
+        Ok(transitions)
+    }
+    /// maps the nodes to 0, 1, ... in the order in which they first appear and checks that they fit in the processes
+    fn map_nodes(&self, records: &[Record], n: ProcessId) -> Result<(usize, HashMap<String, usize>), Error> {
+        let mut ids = HashMap::new();
+        for record in records {
+            let next = ids.len();
+            ids.entry(record.node.clone()).or_insert(next);
+        }
+        if self.first_process as usize + ids.len() > n as usize {
+            return Err(Error::config(self.file.clone(), None, format!("the trace has {} nodes, but only {} processes from process {} on", ids.len(), n.saturating_sub(self.first_process), self.first_process)));
+        }
+        Ok((ids.len(), ids))
+    }
+    fn read_records(&self) -> Result<Vec<Record>, Error> {
+        let file = File::open(&self.file).map_err(|source| Error::Io { file: self.file.clone(), source })?;
+
+        let mut records = Vec::new();
+        for (line_index, line_result) in BufReader::new(file).lines().enumerate() {
+            let line = line_result.map_err(|source| Error::Io { file: self.file.clone(), source })?;
+            let error = |msg: &str| Error::config(self.file.clone(), Some(line_index + 1), msg);
+            if line.trim().is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let content = line.split('\t').map(|c| c.trim()).collect::<Vec<&str>>();
+            let (node, up, start, end) = match self.format {
+                TraceFormat::Intervals => {
+                    if content.len() < 3 {
+                        return Err(error("expected node, start and end separated by '\\t'"));
+                    }
+                    (content[0], true, content[1], content[2])
+                },
+                TraceFormat::Fta => {
+                    if content.len() < 8 {
+                        return Err(error("expected the columns of an FTA event trace separated by '\\t'"));
+                    }
+                    let up = match content[5] {
+                        "1" => true,
+                        "0" => false,
+                        _ => return Err(error("event_type must be 1 (availability) or 0 (unavailability)"))
+                    };
+                    (content[2], up, content[6], content[7])
+                }
+            };
+            let start = start.parse::<f64>().map_err(|_| error("start must be a number"))?;
+            let end = end.parse::<f64>().map_err(|_| error("end must be a number"))?;
+            if end < start {
+                return Err(error("end must not be before start"));
+            }
+            records.push(Record { node: node.to_string(), start, end, up });
+        }
+        if records.is_empty() {
+            return Err(Error::config(self.file.clone(), None, "the trace is empty"));
+        }
+        Ok(records)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::simulation::TimeUnit;
+    use crate::simulation::test::assert_config_error;
+    use crate::simulation::trace::{TraceConf, TraceFormat, Transition};
+
+    fn transitions(trace: &TraceConf, n: u32) -> Vec<(i64, u32, bool)> {
+        trace.get_transitions(1000, TimeUnit::Ms, n).unwrap().into_iter().map(|Transition { ts, id, up }| (ts, id, up)).collect()
+    }
+
+    #[test]
+    fn test_intervals_trace() {
+        let mut trace: TraceConf = serde_yaml::from_str("{file: config/test/trace-intervals.txt, time_unit: s}").unwrap();
+        assert_eq!(trace.format, TraceFormat::Intervals);
+        assert_eq!(transitions(&trace, 3), vec![(1000, 1, false), (6000, 1, true), (11000, 0, false), (16000, 0, true),
+                                                (21000, 0, false), (21000, 1, false), (21000, 2, false)]);
+
+        trace.time_scale = 0.5;
+        trace.first_process = 2;
+        assert_eq!(transitions(&trace, 5), vec![(1000, 3, false), (3500, 3, true), (6000, 2, false), (8500, 2, true),
+                                                (11000, 2, false), (11000, 3, false), (11000, 4, false)]);
+        assert!(trace.get_transitions(0, TimeUnit::Ms, 4).is_err());
+
+        trace.time_scale = 1.0;
+        trace.first_process = 0;
+        trace.repeat = true;
+        assert!(trace.get_transitions(0, TimeUnit::Ms, 3).is_err());
+        trace.until = Some(45000);
+        assert_eq!(transitions(&trace, 3), vec![(1000, 1, false), (6000, 1, true), (11000, 0, false), (16000, 0, true),
+                                                (21000, 1, false), (26000, 1, true), (31000, 0, false), (36000, 0, true),
+                                                (41000, 1, false)]);
+
+        // the intervals of a node are not necessarily in order in the file
+        let trace: TraceConf = serde_yaml::from_str("{file: config/test/trace-unsorted.txt, time_unit: s}").unwrap();
+        assert_eq!(transitions(&trace, 2), vec![(6000, 0, false), (11000, 0, true), (21000, 0, false), (21000, 1, false)]);
+    }
+
+    #[test]
+    fn test_fta_trace() {
+        let trace: TraceConf = serde_yaml::from_str("{file: config/test/trace-fta.tab, format: fta, time_unit: s, time_scale: 2.0}").unwrap();
+        assert_eq!(transitions(&trace, 2), vec![(1000, 1, false), (101_000, 1, true), (201_000, 0, false), (302_000, 0, true)]);
+
+        let trace: TraceConf = serde_yaml::from_str("{file: config/test/trace-intervals.txt, format: fta}").unwrap();
+        assert_config_error(trace.get_transitions(0, TimeUnit::Ms, 3), Some(2));
+        assert!(serde_yaml::from_str::<TraceConf>("{file: trace.csv, format: csv}").is_err());
+    }
+}
@@ -15,36 +15,38 @@ use serde_yaml;
 use bincode::{serialize, deserialize};
 
 use crate::simulation::ProcessId;
+use crate::Error;
 
-pub fn save_to_file<P: AsRef<Path>, S: Into<String> + Debug + Display>(filename: P, content: S, append: bool) {
+fn io_error<P: AsRef<Path>>(filename: P) -> impl FnOnce(::std::io::Error) -> Error {
+    let file = filename.as_ref().display().to_string();
+    move |source| Error::Io { file, source }
+}
+
+pub fn save_to_file<P: AsRef<Path>, S: Into<String> + Debug + Display>(filename: P, content: S, append: bool) -> Result<(), Error> {
     let mut file = OpenOptions::new()
         .write(true)
         .append(append)
         .create(true)
-        .open(filename)
-        .unwrap();
+        .open(&filename)
+        .map_err(io_error(&filename))?;
 
-    writeln!(file, "{}", content).unwrap();
+    writeln!(file, "{}", content).map_err(io_error(&filename))
 }
 
-pub fn save_u8_to_file<P: AsRef<Path>>(filename: P, content: &[u8], append: bool) {
+pub fn save_u8_to_file<P: AsRef<Path>>(filename: P, content: &[u8], append: bool) -> Result<(), Error> {
     let mut file = OpenOptions::new()
         .write(true)
         .append(append)
         .create(true)
-        .open(filename)
-        .unwrap();
+        .open(&filename)
+        .map_err(io_error(&filename))?;
 
-    file.write(content).unwrap();
+    file.write_all(content).map_err(io_error(&filename))
 }
 
 pub fn vec_to_str_no_whitespace(v: Vec<ProcessId>) -> String {
-    let mut s: String = "[".to_string();
-    for i in 0..v.len()-1 {
-        s += &format!("{},", v[i]);
-    }
-    s += &format!("{}]", v[v.len()-1]);
-    s
+    let items: Vec<String> = v.iter().map(|id| id.to_string()).collect();
+    format!("[{}]", items.join(","))
 }
 
 pub fn vec_to_one_item_per_line<T: Display>(v: &Vec<T>) -> String {
@@ -55,30 +57,34 @@ pub fn vec_to_one_item_per_line<T: Display>(v: &Vec<T>) -> String {
     s
 }
 
-pub fn yaml_from_file_to_object<P: AsRef<Path> + Display, O: serde::de::DeserializeOwned + Debug>(filename: P) -> O {
-    let s = fs::read_to_string(&filename).expect(&format!("Unable to open and read from file {}", &filename));
+/// scrambles the bits of x (the finalizer of SplitMix64), for e.g. to get a pseudo-random value from a seed and a counter
+pub fn mix64(x: u64) -> u64 {
+    let mut z = x.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
 
-    let c = serde_yaml::from_str(&s).unwrap();
+pub fn yaml_from_file_to_object<P: AsRef<Path> + Display, O: serde::de::DeserializeOwned + Debug>(filename: P) -> Result<O, Error> {
+    let s = fs::read_to_string(&filename).map_err(io_error(&filename))?;
 
-    c
+    serde_yaml::from_str(&s).map_err(|source| Error::Yaml { file: filename.to_string(), source })
 }
 
-pub fn save_object_in_yaml_file<O: Serialize, P: AsRef<Path>>(obj: &O, filename: P) {
-    let serialized = serde_yaml::to_string(&obj).unwrap();
+pub fn save_object_in_yaml_file<O: Serialize, P: AsRef<Path>>(obj: &O, filename: P) -> Result<(), Error> {
+    let serialized = serde_yaml::to_string(&obj).map_err(|source| Error::Yaml { file: filename.as_ref().display().to_string(), source })?;
 
-    save_to_file(filename, serialized, false);
+    save_to_file(filename, serialized, false)
 }
 
-pub fn binary_from_file_to_object<P: AsRef<Path> + Display, O: serde::de::DeserializeOwned + Debug>(filename: P) -> O {
-    let s = fs::read(&filename).expect(&format!("Unable to open and read from file {}", &filename));
-
-    let decoded: O = deserialize(&s[..]).unwrap();
+pub fn binary_from_file_to_object<P: AsRef<Path> + Display, O: serde::de::DeserializeOwned + Debug>(filename: P) -> Result<O, Error> {
+    let s = fs::read(&filename).map_err(io_error(&filename))?;
 
-    decoded
+    deserialize(&s[..]).map_err(|source| Error::Checkpoint { file: filename.to_string(), source })
 }
 
-pub fn save_object_in_binary_file<O: Serialize, P: AsRef<Path>>(obj: &O, filename: P) {
-    let encoded: Vec<u8> = serialize(&obj).unwrap();
+pub fn save_object_in_binary_file<O: Serialize, P: AsRef<Path>>(obj: &O, filename: P) -> Result<(), Error> {
+    let encoded: Vec<u8> = serialize(&obj).map_err(|source| Error::Checkpoint { file: filename.as_ref().display().to_string(), source })?;
 
-    save_u8_to_file(filename, &encoded, false);
+    save_u8_to_file(filename, &encoded, false)
 }
@@ -6,7 +6,7 @@
 use std::fmt;
 
 use rand::prelude::*;
-use rand::distributions::{Normal, Uniform, Weibull, LogNormal, Distribution};
+use rand::distributions::{Normal, Uniform, Weibull, LogNormal, Exp, Pareto, Distribution};
 
 use serde::{Serialize};
 use serde::de::{self, Deserialize, Deserializer, Visitor, SeqAccess, MapAccess};
@@ -98,6 +98,45 @@ impl DistributionWrapper for LogNormalWrapper {
     }
 }
 
+#[derive(Debug, Clone, Serialize)]
+pub struct ExpWrapper {
+    #[serde(skip_serializing)]
+    exp: Exp,
+    lambda: f64
+}
+
+impl ExpWrapper {
+    pub fn new(lambda: f64) -> Self {
+        ExpWrapper { lambda, exp: Exp::new(lambda) }
+    }
+}
+
+impl DistributionWrapper for ExpWrapper {
+    fn sample<R: Rng>(&self, rng: &mut R) -> f64 {
+        self.exp.sample(rng)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ParetoWrapper {
+    #[serde(skip_serializing)]
+    pareto: Pareto,
+    scale: f64,
+    shape: f64
+}
+
+impl ParetoWrapper {
+    pub fn new(scale: f64, shape: f64) -> Self {
+        ParetoWrapper { scale, shape, pareto: Pareto::new(scale, shape) }
+    }
+}
+
+impl DistributionWrapper for ParetoWrapper {
+    fn sample<R: Rng>(&self, rng: &mut R) -> f64 {
+        self.pareto.sample(rng)
+    }
+}
+
 
 impl Default for UniformWrapper {
     fn default() -> Self {
@@ -123,6 +162,18 @@ impl Default for LogNormalWrapper {
     }
 }
 
+impl Default for ExpWrapper {
+    fn default() -> Self {
+        ExpWrapper::new(1.0)
+    }
+}
+
+impl Default for ParetoWrapper {
+    fn default() -> Self {
+        ParetoWrapper::new(1.0, 1.5)
+    }
+}
+
 impl<'de> Deserialize<'de> for NormalWrapper {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
         where
@@ -373,11 +424,48 @@ impl<'de> Deserialize<'de> for LogNormalWrapper {
     }
 }
 
+impl<'de> Deserialize<'de> for ExpWrapper {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        struct ExpWrapperFields {
+            lambda: f64
+        }
+
+        let fields = ExpWrapperFields::deserialize(deserializer)?;
+        if fields.lambda <= 0.0 {
+            return Err(de::Error::custom(format!("lambda must be positive, but it is {}", fields.lambda)));
+        }
+        Ok(ExpWrapper::new(fields.lambda))
+    }
+}
+
+impl<'de> Deserialize<'de> for ParetoWrapper {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        struct ParetoWrapperFields {
+            scale: f64,
+            shape: f64
+        }
+
+        let fields = ParetoWrapperFields::deserialize(deserializer)?;
+        if fields.scale <= 0.0 || fields.shape <= 0.0 {
+            return Err(de::Error::custom(format!("scale and shape must be positive, but they are {} and {}", fields.scale, fields.shape)));
+        }
+        Ok(ParetoWrapper::new(fields.scale, fields.shape))
+    }
+}
+
 #[cfg(test)]
 mod test {
     use rand::prelude::*;
 
-    use crate::simulation::wrappers::{NormalWrapper, UniformWrapper, WeibullWrapper, LogNormalWrapper, DistributionWrapper};
+    use crate::simulation::wrappers::{NormalWrapper, UniformWrapper, WeibullWrapper, LogNormalWrapper, ExpWrapper, ParetoWrapper, DistributionWrapper};
     
     use serde_yaml;
 
@@ -440,4 +528,35 @@ mod test {
         let random_num2 = deserialized.sample(&mut rng2);
         assert_eq!(random_num, random_num2);
     }
+
+    #[test]
+    fn test_exp_wrapper() {
+        let distribution: ExpWrapper = Default::default();
+        let mut rng = StdRng::seed_from_u64(0);
+        let random_num = distribution.sample(&mut rng);
+
+        let serialized = serde_yaml::to_string(&distribution).unwrap();
+
+        let deserialized: ExpWrapper = serde_yaml::from_str(&serialized).unwrap();
+
+        let mut rng2 = StdRng::seed_from_u64(0);
+        let random_num2 = deserialized.sample(&mut rng2);
+        assert_eq!(random_num, random_num2);
+    }
+
+    #[test]
+    fn test_pareto_wrapper() {
+        let distribution: ParetoWrapper = Default::default();
+        let mut rng = StdRng::seed_from_u64(0);
+        let random_num = distribution.sample(&mut rng);
+
+        let serialized = serde_yaml::to_string(&distribution).unwrap();
+
+        let deserialized: ParetoWrapper = serde_yaml::from_str(&serialized).unwrap();
+
+        let mut rng2 = StdRng::seed_from_u64(0);
+        let random_num2 = deserialized.sample(&mut rng2);
+        assert_eq!(random_num, random_num2);
+        assert!(random_num >= 1.0);
+    }
 }
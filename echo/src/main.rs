@@ -7,6 +7,7 @@ extern crate serde;
 #[macro_use] extern crate serde_derive;
 
 use corten::simulation::{Process, SimulationKernel, ApplicationBase, Operation, ProcessId, Time, utils};
+use corten::Error;
 
 use std::any::Any;
 use std::rc::Rc;
@@ -195,8 +196,16 @@ pub fn stats(apps: &Vec<Rc<RefCell<Box<dyn ApplicationBase>>>>)  {
 fn main() {
     //simulation configuration
     let conf_filename = "config/conf-echo.yaml";
+
+    if let Err(e) = run(conf_filename) {
+        eprintln!("Error: {}", e);
+        ::std::process::exit(-1);
+    }
+}
+
+fn run(conf_filename: &str) -> Result<(), Error> {
     //initialize app configuration
-    let app_conf: Rc<AppConf> = Rc::new(utils::yaml_from_file_to_object(&conf_filename));
+    let app_conf: Rc<AppConf> = Rc::new(utils::yaml_from_file_to_object(&conf_filename)?);
 
     //initialize all nodes
     let mut apps = Vec::new();
@@ -206,8 +215,10 @@ fn main() {
                                  app_conf.clone())) as Box<dyn ApplicationBase>)));
     }
     //run the simulation
-    let kernel = SimulationKernel::init(&apps, conf_filename);
+    let kernel = SimulationKernel::init(&apps, conf_filename)?;
 
     //simulation finished, compute stats
     stats(&kernel.get_applications());
-}
\ No newline at end of file
+
+    Ok(())
+}